@@ -3,7 +3,7 @@ use crate::{errorln, print_entry};
 
 use std::error;
 use std::fmt::{self, Display, Formatter};
-use std::io::{self, ErrorKind};
+use std::io;
 
 use clap::Values;
 use std::cmp::{Ord, Ordering, PartialOrd};
@@ -146,7 +146,10 @@ fn show_file<'a>(path: &'a str, filter: TagFilter<'a>) -> ShowResult {
     let path = new_path_safe(path)?;
 
     match path.extension() {
-        Some(ext) if ext == "mp3" => Ok(mp3::show(path, filter)),
+        Some(ext) if ext == "mp3" => {
+            mp3::show(path, filter);
+            Ok(())
+        }
         _ => Err(ShowError::Unsupported),
     }
 }
@@ -157,7 +160,7 @@ fn new_path_safe(string: &str) -> Result<&Path, io::Error> {
     path.metadata()?;
 
     if path.is_dir() {
-        return Err(io::Error::new(ErrorKind::Other, "Is a directory"));
+        return Err(io::Error::other("Is a directory"));
     }
 
     Ok(path)
@@ -0,0 +1,107 @@
+//! Measures the time and memory taken to save a tag to a large file.
+//!
+//! The size of the file can be changed with the `MUSIKR_BENCH_SIZE` environment variable,
+//! in MiB. Memory usage is only reported on Linux, where the peak resident set size can be
+//! reset between each step.
+
+use musikr::id3v2::frames::PrivateFrame;
+use musikr::id3v2::Tag;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant};
+
+const MIB: u64 = 1024 * 1024;
+
+fn main() {
+    let size = env::var("MUSIKR_BENCH_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(256);
+
+    let path = env::temp_dir().join("musikr_bench_save.mp3");
+
+    // Fill the file with some junk audio data. This has to be written out fully, as sparse
+    // files would make moving the audio unrealistically fast.
+    let mut file = BufWriter::new(File::create(&path).unwrap());
+    let chunk: Vec<u8> = (0..MIB).map(|i| (i % 251) as u8).collect();
+
+    for _ in 0..size {
+        file.write_all(&chunk).unwrap();
+    }
+
+    file.flush().unwrap();
+    drop(file);
+    drop(chunk);
+
+    println!("file size: {} MiB", size);
+
+    // Each save is larger than the padding of the last, so the audio has to move every time.
+    // Then, a smaller tag is saved, which should fit into the existing padding. The tag is
+    // built before measuring, but saving still has to render it, so some memory relative to
+    // the tag size is expected. Memory relative to the file size is not.
+    for frame_size in [64 * 1024, 512 * 1024, 4 * MIB as usize, 3 * MIB as usize] {
+        let mut tag = Tag::new();
+
+        tag.frames.add(PrivateFrame {
+            owner: String::from("https://musikr.org"),
+            data: vec![0x16; frame_size],
+        });
+
+        let (elapsed, memory) = measure(|| tag.save(&path).unwrap());
+
+        println!(
+            "saved {} KiB tag in {:?}, {}",
+            frame_size / 1024,
+            elapsed,
+            describe(memory, size)
+        );
+    }
+
+    fs::remove_file(&path).unwrap();
+}
+
+/// Runs `f`, returning how long it took and how much memory it used at its peak beyond
+/// what was already resident, in KiB.
+fn measure(f: impl FnOnce()) -> (Duration, Option<u64>) {
+    let before = reset_peak_memory().and_then(|_| memory_kib("VmRSS:"));
+
+    let start = Instant::now();
+    f();
+    let elapsed = start.elapsed();
+
+    let memory = before
+        .zip(memory_kib("VmHWM:"))
+        .map(|(before, peak)| peak.saturating_sub(before));
+
+    (elapsed, memory)
+}
+
+fn describe(memory: Option<u64>, size: u64) -> String {
+    match memory {
+        Some(memory) => format!(
+            "peak memory: +{} KiB ({:.2}% of the file)",
+            memory,
+            memory as f64 / (size * 1024) as f64 * 100.0
+        ),
+        None => String::from("peak memory: unknown"),
+    }
+}
+
+fn reset_peak_memory() -> Option<()> {
+    // Writing 5 to clear_refs resets VmHWM to the current resident set size.
+    fs::write("/proc/self/clear_refs", "5").ok()
+}
+
+fn memory_kib(field: &str) -> Option<u64> {
+    // VmRSS is the current resident set size of the process, and VmHWM is its peak.
+    fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find(|line| line.starts_with(field))?[field.len()..]
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
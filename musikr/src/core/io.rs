@@ -1,10 +1,13 @@
 /// Shared Tag IO.
 use std::error;
+use std::ffi::OsString;
 use std::fmt::{self, Display, Formatter};
-use std::fs::OpenOptions;
-use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::fs::{self, File, FileTimes, Metadata};
+use std::io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// An ergonomics layer around a byte slice.
 ///
@@ -207,7 +210,7 @@ impl<'a> BufStream<'a> {
     ///
     /// # Errors
     /// If the slice bounds are outside of the range of the stream, then an error is returned.
-    pub fn slice_stream(&mut self, n: usize) -> io::Result<BufStream> {
+    pub fn slice_stream(&mut self, n: usize) -> io::Result<BufStream<'_>> {
         Ok(BufStream::new(self.slice(n)?))
     }
 
@@ -347,40 +350,354 @@ fn oob_error(pos: usize, len: usize) -> io::Error {
     )
 }
 
-/// Replace up to `end` bytes in a file with `data`.
-pub fn write_replaced<P: AsRef<Path>>(path: P, data: &[u8], end: u64) -> io::Result<()> {
-    match data.len() as u64 {
-        len if len == end => {
-            let mut file = OpenOptions::new().create(true).write(true).open(&path)?;
+/// A stream that can be shortened, such as a [`File`](File) or an in-memory buffer.
+///
+/// This is required when writing tags, as a tag may become smaller than the one that it
+/// replaces.
+pub trait Truncate {
+    /// Truncates the stream to `len` bytes.
+    fn truncate(&mut self, len: u64) -> io::Result<()>;
+}
+
+impl Truncate for File {
+    fn truncate(&mut self, len: u64) -> io::Result<()> {
+        self.set_len(len)
+    }
+}
 
-            // The lengths match, we can just blit directly.
-            file.write_all(data)?;
-            file.flush()
+impl Truncate for Cursor<Vec<u8>> {
+    fn truncate(&mut self, len: u64) -> io::Result<()> {
+        self.get_mut().truncate(len as usize);
+        Ok(())
+    }
+}
+
+impl Truncate for Cursor<&mut Vec<u8>> {
+    fn truncate(&mut self, len: u64) -> io::Result<()> {
+        self.get_mut().truncate(len as usize);
+        Ok(())
+    }
+}
+
+impl<T: Truncate + ?Sized> Truncate for &mut T {
+    fn truncate(&mut self, len: u64) -> io::Result<()> {
+        (**self).truncate(len)
+    }
+}
+
+impl<T: Truncate + ?Sized> Truncate for Box<T> {
+    fn truncate(&mut self, len: u64) -> io::Result<()> {
+        (**self).truncate(len)
+    }
+}
+
+/// The size of the buffer used when moving data within a stream.
+const SHIFT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Replace the bytes in `range` of a stream with `data`.
+///
+/// If `data` is the same length as `range`, it will be written in-place. Otherwise, the
+/// rest of the stream is moved with a fixed-size buffer, so that large files never have
+/// to be loaded into memory.
+pub fn write_replaced<F>(file: &mut F, data: &[u8], range: Range<u64>) -> io::Result<()>
+where
+    F: Read + Write + Seek + Truncate + ?Sized,
+{
+    let len = file.seek(SeekFrom::End(0))?;
+
+    // The range may go past the end of a malformed stream, in which case there is
+    // nothing after it to move.
+    let end = u64::min(range.end, len);
+    let new_end = range.start + data.len() as u64;
+
+    if new_end != end {
+        shift(file, end..len, new_end)?;
+    }
+
+    file.seek(SeekFrom::Start(range.start))?;
+    file.write_all(data)?;
+
+    if new_end < end {
+        file.truncate(len - (end - new_end))?;
+    }
+
+    file.flush()
+}
+
+/// Edits the file at `path` with `edit` atomically.
+///
+/// The file is copied to a uniquely named temporary file in the same directory, which is then
+/// edited, synced to disk, and renamed over the original. Symbolic links are resolved first, so
+/// that the file they point to is replaced instead of the link. The permissions and timestamps
+/// of the original are kept, but its owner and group are not, as the copy belongs to whoever
+/// is editing it. If the edit fails, the original file is left untouched. If `backup` is set,
+/// then the original will also be kept with a `.bak` extension.
+pub fn edit_atomic<T, E>(
+    path: &Path,
+    backup: bool,
+    edit: impl FnOnce(&mut File) -> Result<T, E>,
+) -> Result<T, E>
+where
+    E: From<io::Error>,
+{
+    let path = fs::canonicalize(path)?;
+    let metadata = fs::metadata(&path)?;
+    let (temp_path, mut temp) = create_temp(&path)?;
+
+    let result = copy_to_temp(&path, &mut temp)
+        .map_err(E::from)
+        .and_then(|_| edit_temp(temp, &metadata, edit))
+        .and_then(|value| {
+            if backup {
+                make_backup(&path)?;
+            }
+
+            fs::rename(&temp_path, &path)?;
+            sync_dir(&path);
+
+            Ok(value)
+        });
+
+    if result.is_err() {
+        // The original file is still intact, so the half-written copy can be thrown away.
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result
+}
+
+/// Creates a new temporary file next to `path`. Concurrent edits of the same file each get
+/// their own temporary file, so that they can't clobber each other.
+fn create_temp(path: &Path) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    loop {
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let ext = format![".{}-{}.musikr-tmp", process::id(), count];
+        let temp_path = sibling_path(path, &ext);
+
+        let result = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&temp_path);
+
+        match result {
+            Ok(temp) => return Ok((temp_path, temp)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
         }
+    }
+}
+
+fn copy_to_temp(path: &Path, temp: &mut File) -> io::Result<()> {
+    io::copy(&mut File::open(path)?, temp)?;
+    temp.rewind()
+}
 
-        _ => {
-            // The lengths do not match, read the rest of the file and then re-blit all
-            // the data in sequence. This isn't efficent, but theres noting else we can do.
-            let keep = match OpenOptions::new().read(true).open(&path) {
-                Ok(mut file) => {
-                    let mut keep = Vec::with_capacity(file.stream_position()? as usize);
-                    file.seek(SeekFrom::Start(end))?;
-                    file.read_to_end(&mut keep)?;
-                    keep
-                }
-
-                Err(_) => Vec::new(),
-            };
-
-            let mut file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(&path)?;
-
-            file.write_all(data)?;
-            file.write_all(&keep)?;
-            file.flush()
+fn edit_temp<T, E>(
+    mut temp: File,
+    metadata: &Metadata,
+    edit: impl FnOnce(&mut File) -> Result<T, E>,
+) -> Result<T, E>
+where
+    E: From<io::Error>,
+{
+    let value = edit(&mut temp)?;
+
+    temp.set_permissions(metadata.permissions())?;
+    temp.set_times(
+        FileTimes::new()
+            .set_accessed(metadata.accessed()?)
+            .set_modified(metadata.modified()?),
+    )?;
+
+    // The data must be on the disk before the rename, otherwise a crash could leave
+    // an empty file behind.
+    temp.sync_all()?;
+
+    Ok(value)
+}
+
+fn make_backup(path: &Path) -> io::Result<()> {
+    let backup_path = sibling_path(path, ".bak");
+
+    match fs::remove_file(&backup_path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+
+    // The original file is about to be replaced rather than modified, so a hard link
+    // is enough to keep it around. Not every filesystem supports them, however.
+    if fs::hard_link(path, &backup_path).is_err() {
+        fs::copy(path, &backup_path)?;
+    }
+
+    Ok(())
+}
+
+fn sibling_path(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(ext);
+    path.with_file_name(name)
+}
+
+fn sync_dir(path: &Path) {
+    // Syncing the directory makes the rename itself durable. This is only possible on
+    // some platforms, so failures are ignored.
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
         }
     }
 }
+
+/// Moves the bytes in `range` of a stream so that they start at `to`.
+fn shift<F>(file: &mut F, range: Range<u64>, to: u64) -> io::Result<()>
+where
+    F: Read + Write + Seek + ?Sized,
+{
+    let len = range.end - range.start;
+    let mut buf = vec![0; SHIFT_BUFFER_SIZE];
+    let mut moved = 0;
+
+    while moved < len {
+        let chunk = u64::min(len - moved, SHIFT_BUFFER_SIZE as u64);
+
+        // Moving data forward has to start from the end, and moving data backward has to
+        // start from the beginning, so that we never overwrite data that hasn't moved yet.
+        let offset = if to > range.start {
+            len - moved - chunk
+        } else {
+            moved
+        };
+
+        let buf = &mut buf[..chunk as usize];
+
+        file.seek(SeekFrom::Start(range.start + offset))?;
+        file.read_exact(buf)?;
+        file.seek(SeekFrom::Start(to + offset))?;
+        file.write_all(buf)?;
+
+        moved += chunk;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_data() -> Vec<u8> {
+        // Make sure the data spans several buffers and isn't aligned to them.
+        (0..SHIFT_BUFFER_SIZE * 3 + 17)
+            .map(|i| (i % 251) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn write_replaced_same_len() {
+        let data = make_data();
+        let mut cursor = Cursor::new(data.clone());
+
+        write_replaced(&mut cursor, b"abcd", 10..14).unwrap();
+
+        let result = cursor.into_inner();
+        assert_eq!(result.len(), data.len());
+        assert_eq!(&result[..10], &data[..10]);
+        assert_eq!(&result[10..14], b"abcd");
+        assert_eq!(&result[14..], &data[14..]);
+    }
+
+    #[test]
+    fn write_replaced_grow() {
+        let data = make_data();
+        let mut cursor = Cursor::new(data.clone());
+        let new = vec![0xFF; 1000];
+
+        write_replaced(&mut cursor, &new, 10..20).unwrap();
+
+        let result = cursor.into_inner();
+        assert_eq!(result.len(), data.len() + 990);
+        assert_eq!(&result[..10], &data[..10]);
+        assert_eq!(&result[10..1010], &new);
+        assert_eq!(&result[1010..], &data[20..]);
+    }
+
+    #[test]
+    fn write_replaced_shrink() {
+        let data = make_data();
+        let mut cursor = Cursor::new(data.clone());
+
+        write_replaced(&mut cursor, b"abcd", 10..1010).unwrap();
+
+        let result = cursor.into_inner();
+        assert_eq!(result.len(), data.len() - 996);
+        assert_eq!(&result[..10], &data[..10]);
+        assert_eq!(&result[10..14], b"abcd");
+        assert_eq!(&result[14..], &data[1010..]);
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn edit_atomic_failed_edit() {
+        let dir = temp_dir("musikr_atomic_failed");
+        let path = dir.join("file.mp3");
+        fs::write(&path, make_data()).unwrap();
+
+        // The edit fails after the temporary copy has already been changed.
+        let result: io::Result<()> = edit_atomic(&path, true, |file| {
+            file.write_all(b"garbage")?;
+            file.set_len(4)?;
+            Err(io::Error::other("failed"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), make_data());
+
+        // Neither the temporary file nor a backup should be left behind.
+        let entries: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn edit_atomic_symlink() {
+        let dir = temp_dir("musikr_atomic_symlink");
+        let path = dir.join("file.mp3");
+        let link = dir.join("link.mp3");
+        fs::write(&path, make_data()).unwrap();
+        std::os::unix::fs::symlink(&path, &link).unwrap();
+
+        edit_atomic(&link, false, |file| write_replaced(file, b"abcd", 0..0)).unwrap();
+
+        // The link should still point to the file, which should now be edited.
+        let metadata = fs::symlink_metadata(&link).unwrap();
+        assert!(metadata.file_type().is_symlink());
+        assert_eq!(&fs::read(&path).unwrap()[..4], b"abcd");
+        assert_eq!(fs::read(&link).unwrap(), fs::read(&path).unwrap());
+    }
+
+    #[test]
+    fn unique_temp_paths() {
+        let dir = temp_dir("musikr_atomic_temp");
+        let path = dir.join("file.mp3");
+
+        let (a, _) = create_temp(&path).unwrap();
+        let (b, _) = create_temp(&path).unwrap();
+        assert_ne!(a, b);
+    }
+}
@@ -33,13 +33,16 @@
 //! An ID3v2 tag is composed of the following components:
 //!
 //! - A header that contains the version, tag size, and flags. Only the version and tag size
-//! are exposed in the [`Tag`](Tag) type.
+//!   are exposed in the [`Tag`](Tag) type.
 //! - An optional extended header that signifies information about the tag when it was
-//! written. This is exposed with the [`ExtendedHeader`](tag::ExtendedHeader) type.
+//!   written. This is exposed with the [`ExtendedHeader`](tag::ExtendedHeader) type.
 //! - A body made up of "frames", which are the actual tags of the media. This is exposed 
-//! with [`FrameMap`](collections::FrameMap) for known frames, and [`UnknownFrames`](`collections::UnknownFrames`)
-//! for unknown frames.
-//! - On ID3v2.4, a footer might also be present. Musikr does not parse this.
+//!   with [`FrameMap`](collections::FrameMap) for known frames, and [`UnknownFrames`](`collections::UnknownFrames`)
+//!   for unknown frames.
+//! - On ID3v2.4, a footer might also be present. This is a copy of the header that allows
+//!   a tag to be found when it is appended to the end of a file. The placement of a tag can be
+//!   found with [`Tag::placement`](Tag::placement), and both the placement and the footer can be
+//!   changed with [`SaveOptions`](tag::SaveOptions).
 //!
 //! ## Frames
 //!
@@ -74,9 +77,9 @@
 //!
 //! This is because ID3v2 frames are differentiated in two ways:
 //! - A conventional Frame ID, which is the 4 character sequence in the beginning of each key.
-//! This differentiates the frame implementation.
+//!   This differentiates the frame implementation.
 //! - A "key", which is a reflection of what makes the frame "unique" from other frames of
-//! the same type. More information can be found in [`Frame::key`](crate::id3v2::frames::Frame::key).
+//!   the same type. More information can be found in [`Frame::key`](crate::id3v2::frames::Frame::key).
 //!
 //! All indexing methods in [`FrameMap`](collections::FrameMap) rely on the frame key. To access
 //! all frames that match a given Frame ID,  [`FrameMap::get_all`](collections::FrameMap::get_all)
@@ -119,7 +122,9 @@
 //!
 //! Most of musikr's parsing logic cannot be customized. However, custom frame parsing logic can
 //! be added with [`FrameParser`](frames::FrameParser) and [`Tag::open_with_parser`](Tag::open_with_parser).
-//! More information can be found in the [`frames`](frames) module.
+//! More information can be found in the [`frames`](frames) module. Encrypted frames can also be
+//! decrypted with a [`FrameCipher`](crypto::FrameCipher) and [`Tag::open_with_options`](Tag::open_with_options),
+//! while signed frames can be verified with a [`FrameSigner`](crypto::FrameSigner).
 //!
 //! # Tag versioning
 //!
@@ -142,23 +147,35 @@
 
 pub mod collections;
 mod compat;
+pub mod crypto;
+mod restrict;
 #[macro_use]
 mod macros;
 pub mod frames;
 mod syncdata;
 pub mod tag;
 
-use crate::core::io::{write_replaced, BufStream};
+use crate::core::io::{edit_atomic, write_replaced, BufStream, Truncate};
 use collections::{FrameMap, UnknownFrames};
-use frames::{DefaultFrameParser, FrameParser, ParsedFrame};
-use tag::{ExtendedHeader, SaveVersion, TagHeader, Version};
+use frames::text::{Genre, PartNumber};
+use frames::{
+    AudioSeekIndexFrame, CreditsFrame, DefaultFrameParser, FrameFlags, FrameId, FrameNote,
+    FrameParser, ParsedFrame, TextFrame,
+};
+use tag::{
+    CompatibilityPolicy, ExtendedHeader, OpenOptions, Placement, RestrictionChange, SaveOptions,
+    SaveReport, SaveVersion, TagHeader, UpdateReport, Version,
+};
 
 use log::{error, info, warn};
+use std::collections::BTreeMap;
 use std::error;
 use std::fmt::{self, Display, Formatter};
-use std::fs::File;
-use std::io::{self, Read};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 use std::path::Path;
+use std::str;
 
 /// An ID3v2 tag.
 ///
@@ -170,6 +187,12 @@ use std::path::Path;
 #[derive(Debug, Clone)]
 pub struct Tag {
     header: TagHeader,
+    placement: Placement,
+    crc_matches: Option<bool>,
+    signatures_match: Option<bool>,
+    read_only: BTreeMap<String, Vec<u8>>,
+    diagnostics: Vec<Diagnostic>,
+    quirks: Vec<Quirk>,
     /// The tag's extended header. This is optional.
     pub extended_header: Option<ExtendedHeader>,
     /// A collection of known frames found during parsing and/or
@@ -192,6 +215,12 @@ impl Tag {
     pub fn with_version(version: SaveVersion) -> Self {
         Tag {
             header: TagHeader::with_version(Version::from(version)),
+            placement: Placement::Prepend,
+            crc_matches: None,
+            signatures_match: None,
+            read_only: BTreeMap::new(),
+            diagnostics: Vec::new(),
+            quirks: Vec::new(),
             extended_header: None,
             frames: FrameMap::new(),
             unknown_frames: UnknownFrames::new(Version::from(version), Vec::new()),
@@ -202,58 +231,140 @@ impl Tag {
     ///
     /// When parsing frames, [`DefaultFrameParser`](DefaultFrameParser) will be used with
     /// strict mode enabled. If a frame is malformed, then the parsing process will
-    /// stop at that point and return the tag.
+    /// stop at that point and return the tag. The problem will be recorded in
+    /// [`diagnostics`](Tag::diagnostics).
     ///
     /// # Errors
     ///
-    /// If the file cannot be opened, does not contain a tag, or if the tag is malformed, 
-    /// an error will be returned with a general reason for why. Specific information about 
-    /// parsing errors will be logged.
+    /// If the file cannot be opened, does not contain a tag, or if the tag is malformed,
+    /// an error will be returned. Malformed data is reported as a
+    /// [`ParseError::MalformedData`](ParseError::MalformedData), with a [`Malformed`](Malformed)
+    /// describing its [`file_offset`](Malformed::file_offset), [`frame_id`](Malformed::frame_id),
+    /// and [`reason`](Malformed::reason). Frames that could not be read do not cause an error,
+    /// and are recorded in [`Tag::diagnostics`](Tag::diagnostics) and
+    /// [`Tag::quirks`](Tag::quirks) instead.
     pub fn open<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
         Self::open_with_parser(path, &DefaultFrameParser::default())
     }
 
     /// Attempts to open and parse a tag with a [`FrameParser`](FrameParser).
     ///
-    /// The parser will be used to parse and create all frames from the tag. Implementing a 
-    /// custom `FrameParser` can be dangerous, and so it should be avoided in favor of 
-    /// [`DefaultFrameParser`](DefaultFrameParser) when possible. See the documentation of 
+    /// The parser will be used to parse and create all frames from the tag. Implementing a
+    /// custom `FrameParser` can be dangerous, and so it should be avoided in favor of
+    /// [`DefaultFrameParser`](DefaultFrameParser) when possible. See the documentation of
     /// [`FrameParser`](FrameParser) for more information.
     ///
     /// # Errors
     ///
-    /// If the file cannot be opened, does not contain a tag, or if the tag is malformed, 
-    /// an error will be returned with a general reason for why. Specific information about 
-    /// parsing errors will be logged.
+    /// If the file cannot be opened, does not contain a tag, or if the tag is malformed,
+    /// an error will be returned. Malformed data is reported as a
+    /// [`ParseError::MalformedData`](ParseError::MalformedData), with a [`Malformed`](Malformed)
+    /// describing its [`file_offset`](Malformed::file_offset), [`frame_id`](Malformed::frame_id),
+    /// and [`reason`](Malformed::reason). Frames that could not be read do not cause an error,
+    /// and are recorded in [`Tag::diagnostics`](Tag::diagnostics) and
+    /// [`Tag::quirks`](Tag::quirks) instead.
     pub fn open_with_parser<P: AsRef<Path>>(
         path: P,
         parser: &impl FrameParser,
     ) -> ParseResult<Self> {
-        let mut file = File::open(path)?;
+        Self::open_with_options(path, parser, &OpenOptions::default())
+    }
+
+    /// Attempts to open and parse a tag with a [`FrameParser`](FrameParser) and the given
+    /// [`OpenOptions`](crate::id3v2::tag::OpenOptions).
+    ///
+    /// If a [`FrameCipher`](crate::id3v2::crypto::FrameCipher) is provided, then encrypted
+    /// frames will be decrypted and parsed with `parser` once all other frames are read.
+    /// Frames that could not be decrypted will become [`UnknownFrame`](frames::UnknownFrame)
+    /// instances. If a [`FrameSigner`](crate::id3v2::crypto::FrameSigner) is provided, then
+    /// the signatures in the tag will be verified against the frames as they were read.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be opened, does not contain a tag, or if the tag is malformed,
+    /// an error will be returned. Malformed data is reported as a
+    /// [`ParseError::MalformedData`](ParseError::MalformedData), with a [`Malformed`](Malformed)
+    /// describing its [`file_offset`](Malformed::file_offset), [`frame_id`](Malformed::frame_id),
+    /// and [`reason`](Malformed::reason). Frames that could not be read do not cause an error,
+    /// and are recorded in [`Tag::diagnostics`](Tag::diagnostics) and
+    /// [`Tag::quirks`](Tag::quirks) instead.
+    pub fn open_with_options<P: AsRef<Path>>(
+        path: P,
+        parser: &impl FrameParser,
+        options: &OpenOptions,
+    ) -> ParseResult<Self> {
+        Self::read_from_with_options(&mut File::open(path)?, parser, options)
+    }
 
-        // Read and parse the possible ID3v2 header
-        let mut header_raw = [0; 10];
-        file.read_exact(&mut header_raw)?;
+    /// Attempts to read and parse a tag from `reader`.
+    ///
+    /// This is equivalent to [`Tag::open`](Tag::open), but works with any source that can be
+    /// read and seeked, such as an in-memory buffer. Only the tag itself is read, so the rest
+    /// of the source does not have to be loaded.
+    ///
+    /// # Errors
+    ///
+    /// If the source cannot be read, does not contain a tag, or if the tag is malformed,
+    /// an error will be returned. Malformed data is reported as a
+    /// [`ParseError::MalformedData`](ParseError::MalformedData), with a [`Malformed`](Malformed)
+    /// describing its [`file_offset`](Malformed::file_offset), [`frame_id`](Malformed::frame_id),
+    /// and [`reason`](Malformed::reason). Frames that could not be read do not cause an error,
+    /// and are recorded in [`Tag::diagnostics`](Tag::diagnostics) and
+    /// [`Tag::quirks`](Tag::quirks) instead.
+    pub fn read_from<R: Read + Seek>(reader: &mut R) -> ParseResult<Self> {
+        Self::read_from_with_options(
+            reader,
+            &DefaultFrameParser::default(),
+            &OpenOptions::default(),
+        )
+    }
 
-        let mut header = TagHeader::parse(header_raw).map_err(|err| match err {
-            ParseError::MalformedData => ParseError::NotFound,
-            err => err,
-        })?;
+    /// Attempts to read and parse a tag from `reader` with a [`FrameParser`](FrameParser)
+    /// and the given [`OpenOptions`](crate::id3v2::tag::OpenOptions).
+    ///
+    /// See [`Tag::open_with_options`](Tag::open_with_options) for more information.
+    ///
+    /// # Errors
+    ///
+    /// If the source cannot be read, does not contain a tag, or if the tag is malformed,
+    /// an error will be returned. Malformed data is reported as a
+    /// [`ParseError::MalformedData`](ParseError::MalformedData), with a [`Malformed`](Malformed)
+    /// describing its [`file_offset`](Malformed::file_offset), [`frame_id`](Malformed::frame_id),
+    /// and [`reason`](Malformed::reason). Frames that could not be read do not cause an error,
+    /// and are recorded in [`Tag::diagnostics`](Tag::diagnostics) and
+    /// [`Tag::quirks`](Tag::quirks) instead.
+    pub fn read_from_with_options<R: Read + Seek>(
+        reader: &mut R,
+        parser: &impl FrameParser,
+        options: &OpenOptions,
+    ) -> ParseResult<Self> {
+        // Find the tag, either at the start of the source or at the end of it.
+        let TagLocation {
+            mut header,
+            range,
+            placement,
+        } = TagLocation::find(reader)?;
 
         // Then get the full tag data. If the size is invalid, then we will just truncate it.
-        let mut tag_data = vec![0; header.size() as usize];
-        let read = file.read(&mut tag_data)?;
-        tag_data.truncate(read);
+        reader.seek(SeekFrom::Start(range.start + 10))?;
+        let mut tag_data = Vec::new();
+        reader
+            .take(header.size() as u64)
+            .read_to_end(&mut tag_data)?;
 
         let mut stream = BufStream::new(&tag_data);
 
-        // ID3v2.3 tag-specific synchronization, decode the stream here.
-        if header.version() < Version::V24 && header.flags().unsync {
+        // ID3v2.3 tag-specific synchronization, decode the stream here. Offsets into the
+        // decoded data will no longer line up with the file.
+        let decoded = header.version() < Version::V24 && header.flags().unsync;
+
+        if decoded {
             tag_data = syncdata::decode(&mut stream);
             stream = BufStream::new(&tag_data);
         }
 
         let mut extended_header = None;
+        let mut quirks = Vec::new();
 
         if header.flags().extended {
             // Certain taggers will flip the extended header flag without writing one,
@@ -262,26 +373,163 @@ impl Tag {
                 Ok(header) => extended_header = Some(header),
                 Err(_) => {
                     info!("resetting incorrectly-set extended header flag");
-                    header.flags_mut().extended = false
+                    header.flags_mut().extended = false;
+
+                    // The frames start where the extended header would have been.
+                    stream = BufStream::new(&tag_data);
+                    quirks.push(Quirk {
+                        offset: 10,
+                        frame_id: None,
+                        kind: QuirkKind::MissingExtendedHeader,
+                    });
                 }
             }
         }
 
-        // Now try parsing our frames.
+        // Verify the CRC-32 of the body if present. ID3v2.3 excludes the padding from the
+        // CRC, so we must remove that first.
+        let mut crc_matches = None;
+
+        if let Some(ExtendedHeader {
+            crc32: Some(expected),
+            padding_size,
+            ..
+        }) = &extended_header
+        {
+            let mut body = &tag_data[stream.pos()..];
+
+            if header.version() < Version::V24 {
+                let padding = padding_size.unwrap_or_default() as usize;
+                body = &body[..body.len().saturating_sub(padding)];
+            }
+
+            let actual = tag::body_crc(header.version(), body, 0);
+
+            if actual != *expected {
+                warn!(
+                    "CRC-32 mismatch: expected {:#x}, found {:#x}",
+                    expected, actual
+                );
+            }
+
+            crc_matches = Some(actual == *expected);
+        }
+
+        // Now try parsing our frames. Keep track of where they start, as signatures must be
+        // verified against the frames exactly as they were written.
+        let body_start = stream.pos();
         let mut frames = FrameMap::new();
         let mut unknowns = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut notes = Vec::new();
+
+        loop {
+            let start = stream.pos();
+            let result = frames::parse_with_notes(&header, &mut stream, parser, &mut notes);
+
+            let version = header.version();
+            let data = &tag_data[start..];
+            let offset = start as u64 + 10;
+
+            for note in notes.drain(..) {
+                // ID3v2.2 frames in ID3v2.3 tags will only have a valid 3-byte ID.
+                let frame_id =
+                    frames::peek_id(version, data).or_else(|| frames::peek_id(Version::V22, data));
+
+                match note {
+                    FrameNote::Quirk(kind) => quirks.push(Quirk {
+                        offset,
+                        frame_id,
+                        kind,
+                    }),
+                    FrameNote::Malformed(reason) => diagnostics.push(Diagnostic {
+                        offset,
+                        frame_id,
+                        reason,
+                        recovery: Recovery::KeptUnknown,
+                    }),
+                }
+            }
 
-        while let Ok(parsed) = frames::parse(&header, &mut stream, parser) {
-            match parsed {
-                ParsedFrame::Frame(frame) => frames.add_boxed(frame),
-                ParsedFrame::Unknown(unknown) => {
+            match result {
+                Ok(ParsedFrame::Frame(frame, flags)) => frames.add_with_flags(frame, flags),
+                Ok(ParsedFrame::Unknown(unknown)) => {
                     info!("found unknown frame {}", unknown.id_str());
                     unknowns.push(unknown)
                 }
-                ParsedFrame::Dropped => {
+                Ok(ParsedFrame::Dropped) => {
                     // Dropped frames have already moved the stream to the next
                     // frame, so we can skip them.
                 }
+                Err(err) => {
+                    // Padding is the only thing that can start with a zero byte, so there are
+                    // no more frames to find.
+                    if matches!(data.first(), None | Some(0)) {
+                        break;
+                    }
+
+                    let err = match decoded {
+                        true => err,
+                        false => err.at(range.start + offset),
+                    };
+
+                    let frame_id = frames::peek_id(version, data);
+                    let is_header = frames::is_header(version, data);
+
+                    let reason = match &err {
+                        _ if frame_id.is_none() => MalformedReason::BadFrameId,
+                        _ if !is_header => MalformedReason::BadFrameSize,
+                        ParseError::MalformedData(malformed) => malformed.reason,
+                        ParseError::IoError(_) => MalformedReason::UnexpectedEnd,
+                        _ => MalformedReason::BadFrameData,
+                    };
+
+                    let mut diagnostic = Diagnostic {
+                        offset,
+                        frame_id,
+                        reason,
+                        recovery: Recovery::Stopped,
+                    };
+
+                    if !options.lenient {
+                        warn!(
+                            "stopping at malformed frame at tag offset {}: {}",
+                            offset, err
+                        );
+                        diagnostics.push(diagnostic);
+                        break;
+                    }
+
+                    // If the header was fine, then the stream has already moved past the frame
+                    // and we can just drop it. Otherwise, we have to search for the next frame.
+                    let header_len = match version {
+                        Version::V22 => 6,
+                        _ => 10,
+                    };
+
+                    if is_header && stream.pos() > start + header_len {
+                        warn!("dropping malformed frame at tag offset {}: {}", offset, err);
+                        diagnostic.recovery = Recovery::Dropped;
+                        diagnostics.push(diagnostic);
+                        continue;
+                    }
+
+                    match frames::resync(version, data) {
+                        Some(skip) => {
+                            warn!("skipping {} bytes of malformed data", skip);
+                            diagnostic.recovery = Recovery::Resynced;
+                            diagnostics.push(diagnostic);
+
+                            stream = BufStream::new(&tag_data);
+                            stream.skip(start + skip)?;
+                        }
+                        None => {
+                            warn!("could not find any frames after malformed data");
+                            diagnostics.push(diagnostic);
+                            break;
+                        }
+                    }
+                }
             }
         }
 
@@ -289,10 +537,26 @@ impl Tag {
         // 1. To make sure downcasting behavior is consistent
         // 2. To make sure tags of one version don't end up polluted with frames of another
         // version.
+        if let Some(cipher) = options.cipher {
+            unknowns = crypto::decrypt_frames(&header, &mut frames, unknowns, parser, cipher);
+        }
+
         let unknown_frames = UnknownFrames::new(header.version(), unknowns);
 
+        let signatures_match = options.signer.and_then(|signer| {
+            crypto::verify_signatures(&header, &frames, &tag_data[body_start..], signer)
+        });
+
+        let read_only = read_only_frames(&frames);
+
         Ok(Self {
             header,
+            placement,
+            crc_matches,
+            signatures_match,
+            read_only,
+            diagnostics,
+            quirks,
             extended_header,
             frames,
             unknown_frames,
@@ -308,10 +572,179 @@ impl Tag {
         self.header.version()
     }
 
+    /// Returns where this tag is located in a file.
+    ///
+    /// Freshly created tags will always be [`Placement::Prepend`](crate::id3v2::tag::Placement::Prepend).
+    /// This value is only updated when the tag is read or saved.
+    pub fn placement(&self) -> Placement {
+        self.placement
+    }
+
+    /// Returns whether the CRC-32 in the extended header matched the tag body.
+    ///
+    /// `None` is returned if the tag had no CRC-32. This value is only updated when the tag
+    /// is read or saved.
+    pub fn crc_matches(&self) -> Option<bool> {
+        self.crc_matches
+    }
+
+    /// Returns whether every [`SignatureFrame`](frames::SignatureFrame) matched the frames
+    /// of its group.
+    ///
+    /// `None` is returned if the tag had no signatures, or if no [`FrameSigner`](crypto::FrameSigner)
+    /// was provided. This value is only updated when the tag is read or saved.
+    pub fn signatures_match(&self) -> Option<bool> {
+        self.signatures_match
+    }
+
+    /// Returns the problems that were found while reading the tag.
+    ///
+    /// Unless the tag was read with [`OpenOptions::lenient`](crate::id3v2::tag::OpenOptions::lenient)
+    /// enabled, parsing stops at the first malformed frame, which will be the last diagnostic with
+    /// a recovery of [`Recovery::Stopped`](Recovery::Stopped). This value is only updated when
+    /// the tag is read.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the deviations from the spec that were corrected while reading the tag.
+    ///
+    /// This value is only updated when the tag is read.
+    pub fn quirks(&self) -> &[Quirk] {
+        &self.quirks
+    }
+
+    /// Returns the track number from the `TRCK` frame.
+    ///
+    /// See [`TextFrame::part`](frames::TextFrame::part) for how the frame is parsed.
+    pub fn track(&self) -> Option<PartNumber> {
+        self.text_frame(b"TRCK")?.part()
+    }
+
+    /// Writes `track` to the `TRCK` frame, creating it if needed.
+    pub fn set_track(&mut self, track: PartNumber) {
+        self.text_frame_mut(b"TRCK").set_part(track)
+    }
+
+    /// Returns the disc number from the `TPOS` frame.
+    ///
+    /// See [`TextFrame::part`](frames::TextFrame::part) for how the frame is parsed.
+    pub fn disc(&self) -> Option<PartNumber> {
+        self.text_frame(b"TPOS")?.part()
+    }
+
+    /// Writes `disc` to the `TPOS` frame, creating it if needed.
+    pub fn set_disc(&mut self, disc: PartNumber) {
+        self.text_frame_mut(b"TPOS").set_part(disc)
+    }
+
+    /// Returns the iTunes movement number from the `MVIN` frame.
+    ///
+    /// See [`TextFrame::part`](frames::TextFrame::part) for how the frame is parsed.
+    pub fn movement(&self) -> Option<PartNumber> {
+        self.text_frame(b"MVIN")?.part()
+    }
+
+    /// Writes `movement` to the `MVIN` frame, creating it if needed.
+    pub fn set_movement(&mut self, movement: PartNumber) {
+        self.text_frame_mut(b"MVIN").set_part(movement)
+    }
+
+    /// Returns the beats per minute from the `TBPM` frame.
+    ///
+    /// See [`TextFrame::number`](frames::TextFrame::number) for how the frame is parsed.
+    pub fn bpm(&self) -> Option<u64> {
+        self.text_frame(b"TBPM")?.number()
+    }
+
+    /// Writes `bpm` to the `TBPM` frame, creating it if needed.
+    pub fn set_bpm(&mut self, bpm: u64) {
+        self.text_frame_mut(b"TBPM").set_number(bpm)
+    }
+
+    /// Returns the length of the audio in milliseconds from the `TLEN` frame.
+    ///
+    /// See [`TextFrame::number`](frames::TextFrame::number) for how the frame is parsed.
+    pub fn length(&self) -> Option<u64> {
+        self.text_frame(b"TLEN")?.number()
+    }
+
+    /// Writes `length`, in milliseconds, to the `TLEN` frame, creating it if needed.
+    pub fn set_length(&mut self, length: u64) {
+        self.text_frame_mut(b"TLEN").set_number(length)
+    }
+
+    /// Returns the playlist delay in milliseconds from the `TDLY` frame.
+    ///
+    /// See [`TextFrame::number`](frames::TextFrame::number) for how the frame is parsed.
+    pub fn delay(&self) -> Option<u64> {
+        self.text_frame(b"TDLY")?.number()
+    }
+
+    /// Writes `delay`, in milliseconds, to the `TDLY` frame, creating it if needed.
+    pub fn set_delay(&mut self, delay: u64) {
+        self.text_frame_mut(b"TDLY").set_number(delay)
+    }
+
+    /// Returns whether the iTunes `TCMP` frame marks this file as part of a compilation.
+    ///
+    /// Any non-zero number is treated as `true`. See [`TextFrame::number`](frames::TextFrame::number)
+    /// for how the frame is parsed.
+    pub fn compilation(&self) -> Option<bool> {
+        self.text_frame(b"TCMP")?.number().map(|flag| flag != 0)
+    }
+
+    /// Writes `compilation` to the `TCMP` frame as `1` or `0`, creating it if needed.
+    pub fn set_compilation(&mut self, compilation: bool) {
+        self.text_frame_mut(b"TCMP").set_number(compilation as u64)
+    }
+
+    /// Returns the genres in the `TCON` frame.
+    ///
+    /// See [`Genre::parse`](frames::text::Genre::parse) for how the frame is parsed.
+    pub fn genres(&self) -> Vec<Genre> {
+        self.text_frame(b"TCON")
+            .map(TextFrame::genres)
+            .unwrap_or_default()
+    }
+
+    /// Writes `genres` to the `TCON` frame, creating it if needed.
+    pub fn set_genres(&mut self, genres: &[Genre]) {
+        self.text_frame_mut(b"TCON").set_genres(genres)
+    }
+
+    /// Returns everyone credited for `role` in the `TIPL` and `TMCL` frames.
+    ///
+    /// Roles are compared with [`normalize_role`](frames::text::normalize_role), so looking
+    /// up `"bass"` will also find people credited as `"Bassist"`.
+    pub fn credited(&self, role: &str) -> Vec<&str> {
+        ["TIPL", "TMCL"]
+            .iter()
+            .filter_map(|key| self.frames.get(key)?.downcast::<CreditsFrame>())
+            .flat_map(|frame| frame.people.get(role))
+            .collect()
+    }
+
+    fn text_frame(&self, frame_id: &[u8; 4]) -> Option<&TextFrame> {
+        let key = str::from_utf8(frame_id).unwrap();
+        self.frames.get(key)?.downcast()
+    }
+
+    fn text_frame_mut(&mut self, frame_id: &[u8; 4]) -> &mut TextFrame {
+        let key = str::from_utf8(frame_id).unwrap();
+
+        // Replace anything that is not a text frame, as we have no way to write to it.
+        if !matches!(self.frames.get(key), Some(frame) if frame.is::<TextFrame>()) {
+            self.frames.insert(TextFrame::new(FrameId::new(frame_id)));
+        }
+
+        self.frames[key].downcast_mut().unwrap()
+    }
+
     /// Returns the total size of this tag, in bytes.
     ///
-    /// The size includes the extended header, the tag body [e.g all frames], and
-    /// the footer. This value is only updated when the tag is read or saved, so it
+    /// The size includes the extended header and the tag body [e.g all frames and padding],
+    /// but not the header or footer. This value is only updated when the tag is read or saved, so it
     /// may not be accurate to the current contents of a tag. In a freshly created
     /// tag, this value will be `0`.
     pub fn size(&self) -> u32 {
@@ -326,8 +759,8 @@ impl Tag {
     ///
     /// # ID3v2.3 Conversions
     /// ```text
-    /// EQU2 -> Dropped (no sane conversion)
-    /// RVA2 -> Dropped (no sane conversion)
+    /// EQU2 -> EQUA (lossy, only one frame is kept)
+    /// RVA2 -> RVAD (lossy, only one frame is kept)
     /// ASPI -> Dropped (no analogue)
     /// SEEK -> Dropped (no analogue)
     /// SIGN -> Dropped (no analogue)
@@ -338,7 +771,7 @@ impl Tag {
     /// TPRO -> Dropped (no analogue)
     /// TSST -> Dropped (no analogue)
     ///
-    /// Note: iTunes writes these frames to ID3v2.3 tags, but musikr will still drop these.
+    /// Note: iTunes writes these frames to ID3v2.3 tags, so they are kept if the tag is compatible.
     /// TSOA -> Dropped (no analogue)
     /// TSOP -> Dropped (no analogue)
     /// TSOT -> Dropped (no analogue)
@@ -348,12 +781,29 @@ impl Tag {
     /// TMCL -> IPLS
     /// TRDC -> (yyyy)(-MM-dd)(THH:mm):ss
     ///          TYER   TDAT    TIME
+    /// TCON -> TCON: 17 -> (17)
+    /// ```
+    ///
+    /// # Compatible ID3v2.3 Conversions
+    ///
+    /// With [`CompatibilityPolicy::Compatible`](crate::id3v2::tag::CompatibilityPolicy::Compatible),
+    /// the following frames are converted differently. All other frames are handled as above.
+    /// ```text
+    /// TDEN -> TXXX:ENCODINGTIME
+    /// TDRL -> TXXX:RELEASETIME
+    /// TDTG -> TXXX:TAGGINGTIME
+    /// TMOO -> TXXX:MOOD
+    /// TPRO -> TXXX:PRODUCEDNOTICE
+    /// TSST -> TXXX:SETSUBTITLE
+    /// TSOA -> Kept
+    /// TSOP -> Kept
+    /// TSOT -> Kept
     /// ```
     ///
     /// # ID3v2.4 Conversions
     /// ```text
-    /// EQUA -> Dropped (no sane conversion)
-    /// RVAD -> Dropped (no sane conversion)
+    /// EQUA -> EQU2 (lossy)
+    /// RVAD -> RVA2 (lossy)
     /// TRDA -> Dropped (no sane conversion)
     /// TSIZ -> Dropped (no analogue)
     /// IPLS -> TIPL
@@ -361,11 +811,48 @@ impl Tag {
     /// TDAT -> TDRC:  yyyy -(MM-dd) THH:mm :ss
     /// TIME -> TDRC:  yyyy - MM-dd (THH:mm):ss
     /// TORY -> TDOR: (yyyy)- MM-dd  THH:mm :ss
+    /// TCON -> TCON: (17)Rock -> 17
     /// ```
-    pub fn update(&mut self, to: SaveVersion) {
+    ///
+    /// # Volume Adjustments
+    ///
+    /// ID3v2.3 never defines the units of RVAD and EQUA, so musikr assumes that their volumes
+    /// are 16-bit fixed-point changes in amplitude, where `32768` doubles or silences a channel.
+    /// Conversions between them and RVA2/EQU2 are lossy:
+    /// - Volumes are rounded to the precision of the target frame, and RVA2 volumes are
+    ///   limited to +/- 64 dB.
+    /// - EQU2 frequencies are rounded down to the nearest hz, and the interpolation method is
+    ///   discarded.
+    /// - Only one RVA2/EQU2 frame is kept, preferring the frame with no description and then
+    ///   the frame described as "track". RVA2 channels that RVAD can't represent fall back
+    ///   to the master volume or are discarded.
+    ///
+    /// # Timestamps
+    ///
+    /// Timestamps are converted with [`Timestamp`](crate::id3v2::frames::text::Timestamp).
+    /// If a timestamp or one of its ID3v2.3 parts is malformed, it is recorded in the returned
+    /// [`UpdateReport`](crate::id3v2::tag::UpdateReport) and only the fields that were valid
+    /// are converted.
+    pub fn update(&mut self, to: SaveVersion) -> UpdateReport {
+        self.update_with(to, CompatibilityPolicy::Strict)
+    }
+
+    /// Update the tag to the specified version with the given
+    /// [`CompatibilityPolicy`](crate::id3v2::tag::CompatibilityPolicy).
+    ///
+    /// See [`Tag::update`](Tag::update) for the conversions that are made.
+    pub fn update_with(&mut self, to: SaveVersion, policy: CompatibilityPolicy) -> UpdateReport {
+        let mut report = UpdateReport::default();
+
         match to {
-            SaveVersion::V23 => compat::to_v3(&mut self.frames),
-            SaveVersion::V24 => compat::to_v4(&mut self.frames),
+            SaveVersion::V23 => compat::to_v3(&mut self.frames, policy, &mut report),
+            SaveVersion::V24 => compat::to_v4(&mut self.frames, &mut report),
+        }
+
+        // Genres are only written differently in ID3v2.4, so they are left alone otherwise.
+        // This way, saving a tag without changing its version won't rewrite them.
+        if (self.version() == Version::V24) != (to == SaveVersion::V24) {
+            compat::convert_genres(&mut self.frames, to);
         }
 
         if let Some(ext) = &mut self.extended_header {
@@ -373,6 +860,8 @@ impl Tag {
         }
 
         *self.header.version_mut() = Version::from(to);
+
+        report
     }
 
     /// Clears the tag.
@@ -386,88 +875,444 @@ impl Tag {
 
     /// Saves the tag to `path`.
     ///
-    /// [`Tag::update`](Tag::update) will be called with either the tag's current version in
-    /// the case of ID3v2.3/ID3v2.4, or to ID3v2.3 in the case of ID3v2.2.
+    /// This is equivalent to [`Tag::save_with`](Tag::save_with) with the default
+    /// [`SaveOptions`](crate::id3v2::tag::SaveOptions), which will keep the tag's placement
+    /// and footer.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> SaveResult<()> {
+        self.save_with(path, &SaveOptions::default()).map(|_| ())
+    }
+
+    /// Saves the tag to `path` with the given [`SaveOptions`](crate::id3v2::tag::SaveOptions).
+    ///
+    /// [`Tag::update_with`](Tag::update_with) will be called with either the tag's current version
+    /// in the case of ID3v2.3/ID3v2.4, or to ID3v2.3 in the case of ID3v2.2. The
+    /// [`SaveOptions::compatibility`](crate::id3v2::tag::SaveOptions::compatibility) policy is
+    /// used for ID3v2.3 tags.
     ///
-    /// All known frames will be written, while unknown frames will be written only if [`Tag::version`](Tag::version)
-    /// is equal to [`UnknownFrames::version`](crate::id3v2::collections::UnknownFrames::version).
-    /// No unsynchronization, compression, or similar manipulation is done on the tag body, and
-    /// all flags will be zeroed.
+    /// All known frames will be written. Unknown frames will be converted to [`Tag::version`](Tag::version)
+    /// if it differs from [`UnknownFrames::version`](crate::id3v2::collections::UnknownFrames::version),
+    /// as described in [`SaveOptions::legacy_ids`](crate::id3v2::tag::SaveOptions::legacy_ids).
+    /// Unsynchronization and compression will only be applied if they are enabled in `options`.
+    /// All other flags besides the footer flag will be zeroed.
     ///
     /// The tag will be written to the file regardless of if a previous tag is present. If the tag
     /// is written to a file that may not support ID3v2, this may render the file inoperable.
-    /// If the written tag is smaller than a pre-existing tag, at most 1% of the file size will be
-    /// used for padding. If the tag is larger, then 1 KiB of padding will be applied.
+    /// If a previous tag is present at a different placement, then it will be removed.
+    /// Appended tags will be written before an ID3v1 tag if one is present.
+    ///
+    /// The amount of padding is decided by [`SaveOptions::padding`](crate::id3v2::tag::SaveOptions::padding).
+    /// By default, if the written tag is smaller than a pre-existing tag, at most 1% of the file
+    /// size will be used for padding. If the tag is larger, then 1 KiB of padding will be applied.
+    /// Tags with a footer will never be padded.
+    ///
+    /// If [`SaveOptions::atomic`](crate::id3v2::tag::SaveOptions::atomic) is set, the tag is
+    /// written to a copy of the file that then replaces the original, so that a crash or a full
+    /// disk will never leave the file half-written.
+    ///
+    /// When the tag fits within the padding of the pre-existing tag, it is written in-place and
+    /// the rest of the file is left untouched. Otherwise, the audio data is moved in small chunks,
+    /// so that the file is never fully loaded into memory.
+    ///
+    /// If the audio data moves as a result of the save, any [`AudioSeekIndexFrame`](frames::AudioSeekIndexFrame)
+    /// will be shifted to point to the new location of the audio. [`SeekFrame`](frames::SeekFrame)s
+    /// will be dropped if the tag moves to a different placement, as their offset would no longer
+    /// be valid.
+    ///
+    /// If a [`FrameSigner`](crate::id3v2::crypto::FrameSigner) is provided, every
+    /// [`SignatureFrame`](frames::SignatureFrame) will be re-signed and written after all
+    /// other frames.
+    ///
+    /// The status flags of each frame are respected. Tags with edited or removed read-only frames
+    /// will not be saved unless [`SaveOptions::edit_read_only`](crate::id3v2::tag::SaveOptions::edit_read_only)
+    /// is set, and frames that must be discarded when the file is altered will be dropped if
+    /// [`SaveOptions::file_altered`](crate::id3v2::tag::SaveOptions::file_altered) is set. Unknown
+    /// frames that must be discarded when the tag is altered are always dropped.
+    ///
+    /// If restrictions are enforced, then any changes made to the tag will be returned in a
+    /// [`SaveReport`](crate::id3v2::tag::SaveReport). Otherwise, the report will be empty.
     ///
     /// If the tag creation or writing process fails, then an error with a general reason will
     /// be returned.  Specific information about saving errors will be logged.
-    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> SaveResult<()> {
+    pub fn save_with<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        options: &SaveOptions,
+    ) -> SaveResult<SaveReport> {
+        let path = path.as_ref();
+
+        if options.atomic && path.exists() {
+            return edit_atomic(path, options.backup, |file| self.write_to(file, options));
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(path)?;
+
+        self.write_to(&mut file, options)
+    }
+
+    /// Writes the tag to `file` with the given [`SaveOptions`](crate::id3v2::tag::SaveOptions).
+    ///
+    /// This is equivalent to [`Tag::save_with`](Tag::save_with), but works with any source
+    /// that can be read, written, seeked, and [truncated](crate::core::Truncate), such as
+    /// an in-memory buffer. `file` is treated as the entire contents of a file, with any
+    /// previous tag in it being replaced.
+    ///
+    /// # Errors
+    ///
+    /// If the tag creation or writing process fails, then an error with a general reason will
+    /// be returned.  Specific information about saving errors will be logged.
+    pub fn write_to<F>(&mut self, file: &mut F, options: &SaveOptions) -> SaveResult<SaveReport>
+    where
+        F: Read + Write + Seek + Truncate,
+    {
+        // Restrictions alter the frames to make them conform, and read-only frames are only
+        // known to be intact once the tag has been converted and restricted. Neither should
+        // leave the tag altered if it ends up not being written.
+        let restricted = options.restrict
+            && matches!(&self.extended_header, Some(ext) if ext.restrictions.is_some());
+        let guarded = !options.edit_read_only && !self.read_only.is_empty();
+
+        let original = (restricted || guarded).then(|| self.clone());
+        let result = self.write_tag(file, options);
+
+        if let (Err(_), Some(original)) = (&result, original) {
+            *self = original;
+        }
+
+        result
+    }
+
+    /// Returns an error if any read-only frame differs from when it was last read or saved.
+    fn check_read_only(&self) -> SaveResult<()> {
+        let current = read_only_frames(&self.frames);
+
+        for (key, data) in &self.read_only {
+            if current.get(key) != Some(data) {
+                error!("read-only frame {} was edited", key);
+                return Err(SaveError::ReadOnly);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_tag<F>(&mut self, file: &mut F, options: &SaveOptions) -> SaveResult<SaveReport>
+    where
+        F: Read + Write + Seek + Truncate,
+    {
+        // Read-only frames are checked before anything else can change them, and then again
+        // once the tag has been converted and restricted.
+        if !options.edit_read_only {
+            self.check_read_only()?;
+        }
+
         // Before saving, ensure that our tag has been fully upgraded. ID3v2.2 tags always
         // become ID3v2.3 tags, as it has been obsoleted.
-        match self.header.version() {
-            Version::V22 | Version::V23 => self.update(SaveVersion::V23),
-            Version::V24 => self.update(SaveVersion::V24),
+        let policy = options.compatibility;
+
+        let update = match self.header.version() {
+            Version::V22 | Version::V23 => self.update_with(SaveVersion::V23, policy),
+            Version::V24 => self.update_with(SaveVersion::V24, policy),
+        };
+
+        // Appended tags and footers are an ID3v2.4 addition.
+        let mut placement = options.placement.unwrap_or(self.placement);
+
+        if placement == Placement::Append && self.version() != Version::V24 {
+            warn!(
+                "cannot append a {} tag, will be prepended instead",
+                self.version()
+            );
+            placement = Placement::Prepend;
+        }
+
+        let footer = self.version() == Version::V24
+            && (placement == Placement::Append
+                || options.footer.unwrap_or(self.header.flags().footer));
+
+        let mut report = SaveReport {
+            update,
+            ..Default::default()
+        };
+
+        // Restrictions can only be present on ID3v2.4 tags.
+        let restrictions = match &self.extended_header {
+            Some(ext) if options.restrict && self.version() == Version::V24 => ext.restrictions,
+            _ => None,
         };
 
+        if let Some(restrictions) = &restrictions {
+            report.restrictions = restrict::apply(&mut self.frames, restrictions);
+        }
+
+        if !options.edit_read_only {
+            self.check_read_only()?;
+        }
+
+        // A CRC-32 can only be written to an extended header.
+        if options.crc32 {
+            let ext = self.extended_header.get_or_insert_with(Default::default);
+            ext.crc32.get_or_insert(0);
+        }
+
         // Reset all the flags that we don't really have a way to expose or support.
         let flags = self.header.flags_mut();
-        flags.unsync = false; // Modern software is aware of ID3v2, making this obsolete
+        flags.unsync = options.unsync; // Obsolete, but some old software still requires it
         flags.extended = self.extended_header.is_some(); // Supported
         flags.experimental = false; // This has no use defined by the spec
-        flags.footer = false; // May be exposed in the future
+        flags.footer = footer; // Supported
+
+        // Frames that depend on the audio data are no longer valid once it has been altered.
+        if options.file_altered {
+            let discarded: Vec<String> = self
+                .frames
+                .keys()
+                .filter(|key| {
+                    self.frames
+                        .flags(key)
+                        .unwrap_or_default()
+                        .discard_on_file_alter
+                })
+                .cloned()
+                .collect();
+
+            for key in discarded {
+                info!("dropping frame {}, as the file was altered", key);
+                self.frames.remove(&key);
+            }
+        }
 
-        // Render the extended header first, if it's present.
-        let mut tag_data = match &self.extended_header {
-            Some(ext) => ext.render(self.header.version()),
-            None => Vec::new(),
+        // Unknown frames can only be moved to another version if their data does not depend
+        // on it. Since we are altering the tag, unknown frames that ask to be discarded are
+        // dropped.
+        let mut unknown_data = Vec::new();
+        let mut unknown_count = 0;
+
+        for frame in self
+            .unknown_frames
+            .migrate(self.version(), options.legacy_ids)
+        {
+            let flags = FrameFlags::from_status(self.version(), frame.flags());
+
+            if flags.discard_on_tag_alter || (options.file_altered && flags.discard_on_file_alter) {
+                info!("dropping unknown frame {}", frame.id_str());
+                continue;
+            }
+
+            unknown_data.extend(frames::render_unknown(&self.header, &frame));
+            unknown_count += 1;
+        }
+
+        // The length of the extended header does not depend on the tag body, so we can
+        // find it here to determine the padding.
+        let ext_len = match &self.extended_header {
+            Some(ext) => ext.render(self.version()).len(),
+            None => 0,
+        };
+
+        // The header and footer are included in the total tag size, which is relevant for
+        // tag size restrictions.
+        let overhead = if footer { 20 } else { 10 };
+
+        // Check if theres an existing tag in this file or not.
+        // If there is, keep track of its size so that we can replace it with this tag.
+        let mut len = file.seek(SeekFrom::End(0))?;
+        let mut end = audio_end(file)?;
+        let mut old = None;
+
+        if let Ok(location) = TagLocation::find(file) {
+            info!("found previously written tag, will be overwritten");
+            old = Some(location)
+        }
+
+        // Keep track of where the audio currently starts, so that we can tell how far it
+        // will move once the new tag is written.
+        let audio_start = match &old {
+            Some(location) if location.placement == Placement::Prepend => location.range.end,
+            _ => 0,
         };
 
-        // Keep track of the body length here so we can tell if we actually wrote frames.
-        let start_len = tag_data.len();
+        // If the old tag is in a different place than the new tag, then it must be removed
+        // before we can write the new tag. This is deferred until nothing else can fail.
+        let removed = match old.take() {
+            Some(location) if location.placement != placement => {
+                let removed_len = location.range.end - location.range.start;
 
-        tag_data.extend(self.frames.render(&self.header));
+                len -= removed_len;
 
-        // While we could theoretically upgrade unknown frames, its better that we don't
-        // since they could be metaframes and since the flags would also have to be changed.
-        if self.unknown_frames.version() == self.version() {
-            for frame in self.unknown_frames.frames() {
-                tag_data.extend(frames::render_unknown(&self.header, frame))
+                if location.placement == Placement::Prepend {
+                    end -= removed_len;
+                }
+
+                Some(location.range)
             }
-        } else {
-            warn!("dropping {} unknown frames", self.unknown_frames.version())
+
+            location => {
+                old = location;
+                None
+            }
+        };
+
+        let (range, old_size) = match &old {
+            Some(location) => (location.range.clone(), location.header.size() as u64),
+            None => match placement {
+                Placement::Prepend => (0..0, 0),
+                Placement::Append => (end..end, 0),
+            },
+        };
+
+        // The seek offset is relative to where the tag currently is, so it can't survive the
+        // tag being moved.
+        if removed.is_some() && self.frames.contains_any(b"SEEK") {
+            warn!("dropping SEEK frames, as the tag is being moved");
+            self.frames.remove_all(b"SEEK");
         }
 
-        // Check if theres an existing header in this file or not.
-        // If there is, keep track of its size so that we can replace it with this tag.
-        let mut len = 0;
-        let mut old_size = 0;
+        // Signatures cover the other frames exactly as they are written, so they have to be
+        // taken out and rendered after everything else.
+        let mut signatures = match options.signer {
+            Some(_) => self.frames.remove_all_with_flags(b"SIGN"),
+            None => Vec::new(),
+        };
 
-        if let Ok(mut file) = File::open(&path) {
-            len = file.metadata()?.len();
+        let mut signed = None;
+
+        // Render the frames first, as the extended header may depend on them. Audio seek
+        // point indexes point to an absolute offset in the file however, so they have to
+        // move alongside the audio. Since that depends on the size of the tag, we keep
+        // re-rendering until the size settles.
+        let mut shift = 0;
+        let mut passes = 0;
+
+        let (frame_data, mut padding_size, max_padding) = loop {
+            let mut frame_data =
+                self.frames
+                    .render(&self.header, &options.compression, options.cipher)?;
+
+            frame_data.extend(&unknown_data);
+
+            if let (Some(signer), false) = (options.signer, signatures.is_empty()) {
+                let (data, ok) = crypto::sign_frames(
+                    &self.header,
+                    &self.frames,
+                    &frame_data,
+                    &mut signatures,
+                    signer,
+                );
+
+                frame_data.extend(data);
+                signed = Some(ok);
+            }
+
+            // Find a sensible padding length. We make all tag sizes here u64 so that we don't accidentally
+            // overflow while doing this.
+            let tag_size = (ext_len + frame_data.len()) as u64;
 
-            let mut header_raw = [0; 10];
+            // Tags with footers cannot be padded.
+            let padding_size = if footer || frame_data.is_empty() {
+                0
+            } else {
+                options.padding.padding(old_size, tag_size, len)
+            };
 
-            if file.read(&mut header_raw).is_ok() {
-                if let Ok(header) = TagHeader::parse(header_raw) {
-                    info!("found previously written tag, will be overwritten");
+            // Make sure that the padding doesn't push the tag over the size restrictions.
+            // Tags that are too large regardless will be rejected later.
+            let max_padding = match &restrictions {
+                Some(restrictions) => restrictions
+                    .tag_size
+                    .max_size()
+                    .saturating_sub(overhead as u64 + tag_size),
+                None => u64::MAX,
+            };
 
-                    old_size = header.size() as u64
+            let tag_len = match placement {
+                Placement::Prepend if !frame_data.is_empty() => {
+                    (overhead as u64 + tag_size).saturating_add(u64::min(padding_size, max_padding))
                 }
+                _ => 0,
             };
+
+            let delta = tag_len as i64 - audio_start as i64;
+
+            // Compression might make the size of the tag flip-flop, so we can't do this forever.
+            // The index is left as it was rendered on the last pass in that case.
+            if delta != shift && passes == 4 {
+                warn!("could not settle the audio seek point index, it may be inaccurate");
+                break (frame_data, padding_size, max_padding);
+            }
+
+            if delta == shift || !self.shift_seek_index(delta - shift) {
+                break (frame_data, padding_size, max_padding);
+            }
+
+            shift = delta;
+            passes += 1;
+        };
+
+        // Don't leave the frames shifted if saving fails. This is redone once the tag is written.
+        self.shift_seek_index(-shift);
+
+        for (frame, flags) in signatures {
+            self.frames.add_with_flags(frame, flags);
+        }
+
+        // Tag size restrictions can't be sanely enforced by removing frames, so we just
+        // refuse to write the tag instead.
+        if let Some(restrictions) = &restrictions {
+            let count = self.frames.values().filter(|f| !f.is_empty()).count() + unknown_count;
+
+            if count > restrictions.tag_size.max_frames() {
+                error!("tag has {} frames, which exceeds the restrictions", count);
+                return Err(SaveError::Restricted);
+            }
+
+            if (overhead + ext_len + frame_data.len()) as u64 > restrictions.tag_size.max_size() {
+                error!("tag exceeds the size restrictions");
+                return Err(SaveError::Restricted);
+            }
+        }
+
+        if let Some(range) = removed {
+            info!("moving tag to a new location");
+            write_replaced(file, &[], range)?;
         }
 
         // Make sure our tag isn't empty. If it is, then we will just delete the tag.
-        if tag_data.len() > start_len {
-            // Find a sensible padding length. We make all tag sizes here u64 so that we don't accidentally
-            // overflow while doing this.
-            let tag_size = tag_data.len() as u64;
+        if !frame_data.is_empty() {
+            if padding_size > max_padding {
+                report.restrictions.push(RestrictionChange::ReducedPadding {
+                    from: padding_size,
+                    to: max_padding,
+                });
+
+                padding_size = max_padding;
+            }
 
-            let padding_size = match u64::checked_sub(old_size, tag_size) {
-                Some(delta) => u64::min(delta, len / 100), // Tag is smaller, use the remaining space or 1% of the file size
-                None => 1024,                              // Tag is larger, use 1KiB.
+            // Now that the padding is known, the extended header can be finalized. This has to
+            // be done before the ID3v2.3 unsynchronization, as the CRC-32 is computed before then.
+            let version = self.header.version();
+
+            let mut tag_data = match &mut self.extended_header {
+                Some(ext) => {
+                    ext.update_body(version, &frame_data, padding_size as usize);
+                    ext.render(version)
+                }
+                None => Vec::new(),
             };
 
-            let tag_size = tag_size + padding_size;
+            tag_data.extend(frame_data);
+
+            // ID3v2.3 unsynchronization is applied to the entire tag body, while ID3v2.4
+            // unsynchronization has already been applied to each frame.
+            if options.unsync && self.version() == Version::V23 {
+                tag_data = syncdata::encode(&tag_data);
+            }
+
+            let tag_size = (tag_data.len() as u64).saturating_add(padding_size);
 
             // Tag sizes are syncsafe, so tags can never be more than 256mb. This also ensures that we won't overflow the
             // u32 when we cast it.
@@ -478,46 +1323,218 @@ impl Tag {
 
             *self.header.size_mut() = tag_size as u32;
 
-            // Finalize our tag, adding the padding and prepending the header.
+            // Finalize our tag, adding the padding and prepending the header. The footer is
+            // appended if needed.
             tag_data.resize(tag_size as usize, 0);
             tag_data.splice(0..0, self.header.render());
 
-            write_replaced(path, &tag_data, old_size + 10)?;
+            if footer {
+                tag_data.extend(self.header.render_footer());
+            }
+
+            write_replaced(file, &tag_data, range)?;
+
+            self.crc_matches = self
+                .extended_header
+                .as_ref()
+                .and_then(|ext| ext.crc32)
+                .map(|_| true);
+
+            self.signatures_match = signed;
         } else {
             info!("tag is empty, deleting tag instead");
 
             *self.header.size_mut() = 0;
+            self.crc_matches = None;
+            self.signatures_match = None;
 
-            write_replaced(&path, &[], old_size + 10)?;
+            write_replaced(file, &[], range)?;
         }
 
-        Ok(())
+        self.shift_seek_index(shift);
+        self.placement = placement;
+        self.read_only = read_only_frames(&self.frames);
+
+        Ok(report)
+    }
+
+    /// Shifts the start of every [`AudioSeekIndexFrame`](frames::AudioSeekIndexFrame) by
+    /// `delta`, returning whether there were any to shift.
+    fn shift_seek_index(&mut self, delta: i64) -> bool {
+        let mut found = false;
+
+        for frame in self.frames.get_all_mut(b"ASPI") {
+            if let Some(aspi) = frame.downcast_mut::<AudioSeekIndexFrame>() {
+                aspi.shift(delta);
+                found = true;
+            }
+        }
+
+        found
     }
 }
 
+/// Renders every read-only frame in `frames`, so that edits to them can be found later on.
+/// A fixed version is used so that the rendered data does not change when the tag is upgraded.
+fn read_only_frames(frames: &FrameMap) -> BTreeMap<String, Vec<u8>> {
+    let header = TagHeader::with_version(Version::V24);
+
+    frames
+        .iter()
+        .filter(|(key, _)| frames.flags(key).unwrap_or_default().read_only)
+        .map(|(key, frame)| (key.to_string(), frame.render(&header)))
+        .collect()
+}
+
 impl Default for Tag {
     fn default() -> Self {
         Self::new()
     }
 }
 
-/// The result given after a parsing operation.
-pub type ParseResult<T> = Result<T, ParseError>;
+/// The location of an existing tag in a file.
+struct TagLocation {
+    header: TagHeader,
+    range: Range<u64>,
+    placement: Placement,
+}
 
-/// The error type returned when parsing ID3v2 tags.
-#[derive(Debug)]
-pub enum ParseError {
-    /// Generic IO errors. This either means that a problem occurred while opening the file
-    /// for a tag, or an unexpected EOF was encountered while parsing.
-    IoError(io::Error),
-    /// A part of the tag was not valid.
-    MalformedData,
-    /// The tag or a element of the tag is unsupported.
-    Unsupported,
-    /// The tag was not found in the given file.
+impl TagLocation {
+    /// Finds a tag in `file`. Tags at the start of a file are preferred, but if none
+    /// are present then the end of the file will be scanned for an ID3v2.4 footer.
+    fn find<R: Read + Seek>(file: &mut R) -> ParseResult<Self> {
+        let mut raw = [0; 10];
+
+        file.seek(SeekFrom::Start(0))?;
+
+        if file.read_exact(&mut raw).is_ok() {
+            match TagHeader::parse(raw) {
+                Ok(header) => {
+                    let mut end = header.size() as u64 + 10;
+
+                    if header.flags().footer {
+                        end += 10;
+                    }
+
+                    return Ok(Self {
+                        header,
+                        range: 0..end,
+                        placement: Placement::Prepend,
+                    });
+                }
+
+                Err(ParseError::NotFound | ParseError::MalformedData(_)) => {
+                    // No tag at the beginning, try the end.
+                }
+
+                Err(err) => return Err(err),
+            }
+        }
+
+        // Appended tags are either at the very end of the file or right before
+        // an ID3v1 tag.
+        let footer_end = audio_end(file)?;
+
+        if footer_end < 10 {
+            return Err(ParseError::NotFound);
+        }
+
+        file.seek(SeekFrom::Start(footer_end - 10))?;
+        file.read_exact(&mut raw)?;
+
+        let footer = TagHeader::parse_footer(raw).map_err(|err| err.at(footer_end - 10))?;
+
+        // Now we can scan backwards to where the header should be.
+        let start = match (footer_end - 10).checked_sub(footer.size() as u64 + 10) {
+            Some(start) => start,
+            None => {
+                error!("footer describes a tag larger than the file");
+                let err = ParseError::malformed(MalformedReason::BadTagHeader);
+                return Err(err.at(footer_end - 10));
+            }
+        };
+
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut raw)?;
+
+        let header = TagHeader::parse(raw).map_err(|err| match err {
+            ParseError::NotFound => ParseError::malformed(MalformedReason::BadTagHeader).at(start),
+            err => err.at(start),
+        })?;
+
+        Ok(Self {
+            header,
+            range: start..footer_end,
+            placement: Placement::Append,
+        })
+    }
+}
+
+/// Returns the position that an appended tag should end at, which is either the end
+/// of the file or the beginning of an ID3v1 tag.
+fn audio_end<R: Read + Seek>(file: &mut R) -> io::Result<u64> {
+    let len = file.seek(SeekFrom::End(0))?;
+
+    if len >= 128 {
+        let mut id = [0; 3];
+        file.seek(SeekFrom::Start(len - 128))?;
+        file.read_exact(&mut id)?;
+
+        if &id == b"TAG" {
+            return Ok(len - 128);
+        }
+    }
+
+    Ok(len)
+}
+
+/// The result given after a parsing operation.
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// The error type returned when parsing ID3v2 tags.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Generic IO errors. This either means that a problem occurred while opening the file
+    /// for a tag, or an unexpected EOF was encountered while parsing.
+    IoError(io::Error),
+    /// A part of the tag was not valid.
+    MalformedData(Malformed),
+    /// The tag or a element of the tag is unsupported.
+    Unsupported,
+    /// The tag was not found in the given file.
     NotFound,
 }
 
+impl ParseError {
+    pub(crate) fn malformed(reason: MalformedReason) -> Self {
+        Self::MalformedData(Malformed {
+            file_offset: None,
+            frame_id: None,
+            reason,
+        })
+    }
+
+    /// Attaches `frame_id` to this error if it does not already describe a frame.
+    pub(crate) fn in_frame(mut self, frame_id: &str) -> Self {
+        if let Self::MalformedData(malformed) = &mut self {
+            malformed
+                .frame_id
+                .get_or_insert_with(|| frame_id.to_string());
+        }
+
+        self
+    }
+
+    /// Attaches `file_offset` to this error if it does not already have one.
+    pub(crate) fn at(mut self, file_offset: u64) -> Self {
+        if let Self::MalformedData(malformed) = &mut self {
+            malformed.file_offset.get_or_insert(file_offset);
+        }
+
+        self
+    }
+}
+
 impl From<io::Error> for ParseError {
     fn from(other: io::Error) -> Self {
         ParseError::IoError(other)
@@ -528,7 +1545,7 @@ impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             Self::IoError(err) => err.fmt(f),
-            Self::MalformedData => write![f, "malformed data"],
+            Self::MalformedData(malformed) => write![f, "malformed data: {}", malformed],
             Self::Unsupported => write![f, "unsupported"],
             Self::NotFound => write![f, "not found"],
         }
@@ -546,6 +1563,131 @@ impl error::Error for ParseError {
     }
 }
 
+/// Where and why a tag was malformed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Malformed {
+    /// The offset of the malformed data from the start of the file, if it is known. Unlike
+    /// [`Diagnostic::offset`](Diagnostic::offset), this is not relative to the tag.
+    pub file_offset: Option<u64>,
+    /// The ID of the frame that was malformed, if it is known.
+    pub frame_id: Option<String>,
+    /// Why the data was malformed.
+    pub reason: MalformedReason,
+}
+
+impl Display for Malformed {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}", self.reason]?;
+
+        if let Some(frame_id) = &self.frame_id {
+            write![f, " in frame {}", frame_id]?;
+        }
+
+        if let Some(file_offset) = self.file_offset {
+            write![f, " at file offset {}", file_offset]?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The reason that part of a tag was malformed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MalformedReason {
+    /// The tag header or footer was invalid.
+    BadTagHeader,
+    /// The extended header was invalid.
+    BadExtendedHeader,
+    /// A frame ID contained characters other than uppercase letters and digits.
+    BadFrameId,
+    /// A frame size extended past the end of the tag.
+    BadFrameSize,
+    /// A frame ended before all of its fields could be read.
+    UnexpectedEnd,
+    /// A frame had a text encoding byte that did not correspond to any encoding.
+    InvalidEncodingByte,
+    /// A compressed frame could not be decompressed.
+    DecompressionFailed,
+    /// A field in a frame had a value that is not allowed by the spec.
+    BadFrameData,
+}
+
+impl Display for MalformedReason {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let desc = match self {
+            Self::BadTagHeader => "bad tag header",
+            Self::BadExtendedHeader => "bad extended header",
+            Self::BadFrameId => "bad frame id",
+            Self::BadFrameSize => "bad frame size",
+            Self::UnexpectedEnd => "unexpected end of frame",
+            Self::InvalidEncodingByte => "invalid encoding byte",
+            Self::DecompressionFailed => "decompression failed",
+            Self::BadFrameData => "bad frame data",
+        };
+
+        write![f, "{}", desc]
+    }
+}
+
+/// A problem found while reading a tag.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// The offset of the problem from the start of the tag, including the tag header. If
+    /// the entire tag was unsynchronized, this is the offset within the decoded tag.
+    pub offset: u64,
+    /// The ID of the frame, if it was valid.
+    pub frame_id: Option<String>,
+    /// What the problem was.
+    pub reason: MalformedReason,
+    /// What the parser did about the problem.
+    pub recovery: Recovery,
+}
+
+/// How the parser recovered from a [`Diagnostic`](Diagnostic).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Recovery {
+    /// The frame was kept as an [`UnknownFrame`](frames::UnknownFrame).
+    KeptUnknown,
+    /// The frame could not be parsed and was dropped.
+    Dropped,
+    /// The frame header was malformed. Parsing resumed at the next frame that could be found.
+    Resynced,
+    /// Parsing stopped, and the rest of the tag was ignored.
+    Stopped,
+}
+
+/// A deviation from the spec that was corrected while reading a tag.
+///
+/// Unlike a [`Diagnostic`](Diagnostic), no data was lost because of a quirk. They are
+/// usually left behind by taggers that misread the spec.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Quirk {
+    /// The offset of the quirk from the start of the tag. See
+    /// [`Diagnostic::offset`](Diagnostic::offset).
+    pub offset: u64,
+    /// The ID of the frame that the quirk was found in, if any.
+    pub frame_id: Option<String>,
+    /// What the quirk was.
+    pub kind: QuirkKind,
+}
+
+/// The kind of a [`Quirk`](Quirk).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuirkKind {
+    /// The extended header flag was set, but there was no extended header. The flag was reset.
+    MissingExtendedHeader,
+    /// An ID3v2.2 frame was written to an ID3v2.3 tag with a zero byte padding its ID. It was
+    /// parsed as an ID3v2.2 frame.
+    LegacyFrameId,
+    /// An ID3v2.4 frame had an ID3v2.3-style size that was not syncsafe. The raw size was used.
+    NonSyncsafeFrameSize,
+    /// An ID3v2.4 frame was compressed without the data length indicator flag being set. The
+    /// data length indicator was read anyway.
+    MissingDataLengthIndicator,
+    /// A frame had a size of zero. It was skipped.
+    EmptyFrame,
+}
+
 /// The result given after a save operation.
 pub type SaveResult<T> = Result<T, SaveError>;
 
@@ -556,6 +1698,12 @@ pub enum SaveError {
     IoError(io::Error),
     /// The tag [or an element in the tag] was too large to be written.
     TooLarge,
+    /// The tag could not be made to conform to its restrictions.
+    Restricted,
+    /// A read-only frame in the tag was edited or removed.
+    ReadOnly,
+    /// A frame marked as encrypted could not be encrypted.
+    Unencrypted,
 }
 
 impl From<io::Error> for SaveError {
@@ -569,6 +1717,9 @@ impl Display for SaveError {
         match self {
             Self::IoError(err) => err.fmt(f),
             Self::TooLarge => write![f, "tag is too large to be saved"],
+            Self::Restricted => write![f, "tag does not conform to its restrictions"],
+            Self::ReadOnly => write![f, "tag has edited read-only frames"],
+            Self::Unencrypted => write![f, "tag has frames that could not be encrypted"],
         }
     }
 }
@@ -610,6 +1761,1170 @@ mod tests {
         id3v22_ensure(&tag, Version::V23);
     }
 
+    #[test]
+    fn read_write_memory() {
+        use std::io::Cursor;
+
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/v22.mp3";
+        let data = std::fs::read(path).unwrap();
+        let mut cursor = Cursor::new(data);
+
+        let mut tag = Tag::read_from(&mut cursor).unwrap();
+        id3v22_ensure(&tag, Version::V22);
+
+        let audio = cursor.get_ref()[tag.size() as usize + 10..].to_vec();
+
+        tag.write_to(&mut cursor, &SaveOptions::default()).unwrap();
+
+        let tag = Tag::read_from(&mut cursor).unwrap();
+        id3v22_ensure(&tag, Version::V23);
+
+        // The audio should be untouched after the new tag.
+        let data = cursor.into_inner();
+        assert!(data.ends_with(&audio));
+
+        // Clearing the tag should leave only the audio.
+        let mut cursor = Cursor::new(data);
+        let mut tag = Tag::new();
+        tag.write_to(&mut cursor, &SaveOptions::default()).unwrap();
+        assert_eq!(cursor.into_inner(), audio);
+    }
+
+    #[test]
+    fn write_atomic() {
+        use crate::id3v2::frames::PrivateFrame;
+        use crate::id3v2::tag::{
+            ImageEncodingRestriction, ImageSizeRestriction, Restrictions, TagSizeRestriction,
+            TextEncodingRestriction, TextSizeRestriction,
+        };
+        use std::time::{Duration, SystemTime};
+
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/v22.mp3";
+        let out = env::temp_dir().join("musikr_atomic_out.mp3");
+        let backup = env::temp_dir().join("musikr_atomic_out.mp3.bak");
+        std::fs::copy(&path, &out).unwrap();
+
+        // Give the file an old modification time, so that we can tell if it was kept.
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let file = File::options().write(true).open(&out).unwrap();
+        file.set_modified(modified).unwrap();
+        drop(file);
+
+        let original = std::fs::read(&out).unwrap();
+        let permissions = std::fs::metadata(&out).unwrap().permissions();
+
+        let mut tag = Tag::open(&out).unwrap();
+        tag.frames.remove("TIT2");
+
+        let options = SaveOptions {
+            atomic: true,
+            backup: true,
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        let metadata = std::fs::metadata(&out).unwrap();
+        assert_eq!(metadata.modified().unwrap(), modified);
+        assert_eq!(metadata.permissions(), permissions);
+        assert!(!Tag::open(&out).unwrap().frames.contains_key("TIT2"));
+        assert_eq!(std::fs::read(&backup).unwrap(), original);
+
+        // Failed saves should leave the file untouched. This tag is too large for its
+        // restrictions, so it can't be written.
+        let mut tag = Tag::open(&out).unwrap();
+        tag.update(SaveVersion::V24);
+
+        tag.extended_header = Some(ExtendedHeader {
+            restrictions: Some(Restrictions {
+                tag_size: TagSizeRestriction::Max32Frames4Kb,
+                text_encoding: TextEncodingRestriction::None,
+                text_size: TextSizeRestriction::None,
+                image_encoding: ImageEncodingRestriction::None,
+                image_size: ImageSizeRestriction::None,
+            }),
+            ..Default::default()
+        });
+
+        tag.frames.add(PrivateFrame {
+            owner: String::from("musikr"),
+            data: vec![0x16; 8192],
+        });
+
+        let options = SaveOptions {
+            restrict: true,
+            ..options
+        };
+
+        let saved = std::fs::read(&out).unwrap();
+        assert!(matches!(
+            tag.save_with(&out, &options),
+            Err(SaveError::Restricted)
+        ));
+        assert_eq!(std::fs::read(&out).unwrap(), saved);
+
+        let temp_files = std::fs::read_dir(env::temp_dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| {
+                name.starts_with("musikr_atomic_out.mp3.") && name.ends_with(".musikr-tmp")
+            });
+
+        assert_eq!(temp_files.count(), 0);
+    }
+
+    #[test]
+    fn write_padding() {
+        use crate::id3v2::tag::PaddingPolicy;
+
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/v22.mp3";
+        let out = env::temp_dir().join("musikr_padding_out.mp3");
+        std::fs::copy(&path, &out).unwrap();
+
+        let mut tag = Tag::open(&out).unwrap();
+
+        let save = |tag: &mut Tag, padding| {
+            let options = SaveOptions {
+                padding,
+                ..Default::default()
+            };
+
+            tag.save_with(&out, &options).unwrap();
+            Tag::open(&out).unwrap().size() as u64
+        };
+
+        let unpadded = save(&mut tag, PaddingPolicy::None);
+        assert_eq!(save(&mut tag, PaddingPolicy::Fixed(4096)), unpadded + 4096);
+
+        // Removing a frame should keep the tag at the same size.
+        let len = std::fs::metadata(&out).unwrap().len();
+        tag.frames.remove("TIT2");
+        assert_eq!(save(&mut tag, PaddingPolicy::Keep), unpadded + 4096);
+        assert_eq!(std::fs::metadata(&out).unwrap().len(), len);
+
+        let custom = |old, new, _| old - new + 16;
+        let size = save(&mut tag, PaddingPolicy::Custom(&custom));
+        assert_eq!(size, unpadded + 4096 + 16);
+
+        let len = std::fs::metadata(&out).unwrap().len();
+        let size = save(&mut tag, PaddingPolicy::Percent(10.0));
+        assert!(size > len / 10);
+    }
+
+    #[test]
+    fn read_lenient() {
+        use std::io::Cursor;
+
+        const DATA: &[u8] = b"ID3\x04\x00\x00\x00\x00\x00\x54\
+                              TIT2\x00\x00\x00\x06\x00\x00\x00Title\
+                              \xFF\xFF\xFFjunk\
+                              TALB\x00\x00\x00\x06\x00\x00\x00Album\
+                              COMM\x00\x00\x00\x02\x00\x00\x00e\
+                              TPE1\x00\x00\x00\x07\x00\x00\x00Artist\
+                              \0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+
+        // Normally, parsing stops at the first malformed frame.
+        let tag = Tag::read_from(&mut Cursor::new(DATA)).unwrap();
+        assert_eq!(tag.frames.len(), 1);
+        assert_eq!(
+            tag.diagnostics(),
+            &[Diagnostic {
+                offset: 26,
+                frame_id: None,
+                reason: MalformedReason::BadFrameId,
+                recovery: Recovery::Stopped,
+            }]
+        );
+
+        let options = OpenOptions {
+            lenient: true,
+            ..Default::default()
+        };
+
+        let parser = DefaultFrameParser::default();
+        let tag = Tag::read_from_with_options(&mut Cursor::new(DATA), &parser, &options).unwrap();
+
+        assert_eq!(tag.frames["TIT2"].to_string(), "Title");
+        assert_eq!(tag.frames["TALB"].to_string(), "Album");
+        assert_eq!(tag.frames["TPE1"].to_string(), "Artist");
+        assert!(!tag.frames.contains_any(b"COMM"));
+
+        assert_eq!(
+            tag.diagnostics(),
+            &[
+                Diagnostic {
+                    offset: 26,
+                    frame_id: None,
+                    reason: MalformedReason::BadFrameId,
+                    recovery: Recovery::Resynced,
+                },
+                Diagnostic {
+                    offset: 49,
+                    frame_id: Some(String::from("COMM")),
+                    reason: MalformedReason::UnexpectedEnd,
+                    recovery: Recovery::Dropped,
+                }
+            ]
+        );
+
+        // Malformed data with nothing after it should be skipped entirely.
+        const TRAILING: &[u8] = b"ID3\x04\x00\x00\x00\x00\x00\x18\
+                                  TIT2\x00\x00\x00\x06\x00\x00\x00Title\
+                                  TALB\xFF\xFF\xFF\xFF";
+
+        let tag =
+            Tag::read_from_with_options(&mut Cursor::new(TRAILING), &parser, &options).unwrap();
+
+        assert_eq!(tag.frames.len(), 1);
+        assert_eq!(
+            tag.diagnostics(),
+            &[Diagnostic {
+                offset: 26,
+                frame_id: Some(String::from("TALB")),
+                reason: MalformedReason::BadFrameSize,
+                recovery: Recovery::Stopped,
+            }]
+        );
+    }
+
+    #[test]
+    fn read_quirks() {
+        use std::io::Cursor;
+
+        const DATA: &[u8] = b"ID3\x04\x00\x40\x00\x00\x00\x1F\
+                              TIT2\x00\x00\x00\x06\x00\x00\x00Title\
+                              TALB\x00\x00\x00\x00\x00\x00\
+                              \0\0\0\0\0";
+
+        let tag = Tag::read_from(&mut Cursor::new(DATA)).unwrap();
+
+        assert_eq!(tag.frames["TIT2"].to_string(), "Title");
+        assert!(tag.diagnostics().is_empty());
+        assert_eq!(
+            tag.quirks(),
+            &[
+                Quirk {
+                    offset: 10,
+                    frame_id: None,
+                    kind: QuirkKind::MissingExtendedHeader,
+                },
+                Quirk {
+                    offset: 26,
+                    frame_id: Some(String::from("TALB")),
+                    kind: QuirkKind::EmptyFrame,
+                }
+            ]
+        );
+
+        // A 256-byte frame with an ID3v2.3-style size, which would be read as 128 bytes.
+        let mut data = b"ID3\x04\x00\x00\x00\x00\x02\x1A".to_vec();
+        data.extend(b"PRIV\x00\x00\x01\x00\x00\x00a\0");
+        data.extend([0x16; 254]);
+        data.extend(b"TIT2\x00\x00\x00\x06\x00\x00\x00Title");
+
+        let tag = Tag::read_from(&mut Cursor::new(data)).unwrap();
+
+        assert_eq!(tag.frames["TIT2"].to_string(), "Title");
+        assert_eq!(
+            tag.quirks(),
+            &[Quirk {
+                offset: 10,
+                frame_id: Some(String::from("PRIV")),
+                kind: QuirkKind::NonSyncsafeFrameSize,
+            }]
+        );
+    }
+
+    #[test]
+    fn read_malformed_footer() {
+        use std::io::Cursor;
+
+        // A footer that describes a tag larger than the file.
+        const DATA: &[u8] = b"\x16\x16\x16\x16\x16\x16\x16\x16\x16\x16\
+                              3DI\x04\x00\x10\x00\x00\x01\x00";
+
+        match Tag::read_from(&mut Cursor::new(DATA)) {
+            Err(ParseError::MalformedData(malformed)) => {
+                assert_eq!(malformed.reason, MalformedReason::BadTagHeader);
+                assert_eq!(malformed.file_offset, Some(10));
+            }
+            other => panic!("expected a malformed footer, found {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn numeric_frames() {
+        let mut tag = Tag::new();
+        assert_eq!(tag.track(), None);
+
+        let trck = crate::text_frame!(b"TRCK", Encoding::Latin1, ["03 of 12"]);
+        tag.frames.add(trck);
+        tag.frames.add(crate::text_frame!(b"TCMP", ["1"]));
+
+        assert_eq!(tag.track(), Some(PartNumber::new(3, Some(12))));
+        assert_eq!(tag.compilation(), Some(true));
+
+        tag.set_track(PartNumber::new(4, Some(12)));
+        tag.set_disc(PartNumber::new(1, None));
+        tag.set_movement(PartNumber::new(2, Some(3)));
+        tag.set_bpm(120);
+        tag.set_length(215_000);
+        tag.set_delay(500);
+        tag.set_compilation(false);
+
+        // Existing frames should keep their encoding.
+        let trck = tag.frames["TRCK"].downcast::<TextFrame>().unwrap();
+        assert_eq!(trck.encoding, Encoding::Latin1);
+        assert_eq!(trck.text, &["4/12"]);
+
+        assert_eq!(tag.track(), Some(PartNumber::new(4, Some(12))));
+        assert_eq!(tag.disc(), Some(PartNumber::new(1, None)));
+        assert_eq!(tag.movement(), Some(PartNumber::new(2, Some(3))));
+        assert_eq!(tag.bpm(), Some(120));
+        assert_eq!(tag.length(), Some(215_000));
+        assert_eq!(tag.delay(), Some(500));
+        assert_eq!(tag.compilation(), Some(false));
+        assert_eq!(tag.frames["TPOS"].to_string(), "1");
+        assert_eq!(tag.frames["TCMP"].to_string(), "0");
+    }
+
+    #[test]
+    fn genre_frames() {
+        let mut tag = Tag::new();
+        assert_eq!(tag.genres(), []);
+
+        tag.frames.add(crate::text_frame!(b"TCON", ["(17)(RX)"]));
+        assert_eq!(tag.genres(), [Genre::Id3v1(17), Genre::Remix]);
+
+        tag.set_genres(&[Genre::Id3v1(79), Genre::Name(String::from("Post-Rock"))]);
+        assert_eq!(tag.frames["TCON"].to_string(), "79, Post-Rock");
+    }
+
+    #[test]
+    fn credited_people() {
+        let mut tag = Tag::new();
+        assert_eq!(tag.credited("bass"), Vec::<&str>::new());
+
+        tag.frames.add(crate::credits_frame! {
+            b"TIPL",
+            "Mixer" => "Matt Carver",
+            "Producer" => "Sarah Oliver"
+        });
+
+        tag.frames.add(crate::credits_frame! {
+            b"TMCL",
+            "Bassist" => "John Smith",
+            "Bass" => "Vanessa Evans"
+        });
+
+        assert_eq!(tag.credited("bass"), ["John Smith", "Vanessa Evans"]);
+        assert_eq!(tag.credited("Mixed by"), ["Matt Carver"]);
+    }
+
+    #[test]
+    fn write_appended_tag() {
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/itunes_sizes.mp3";
+        let mut tag = Tag::open(&path).unwrap();
+        assert_eq!(tag.placement(), Placement::Prepend);
+
+        let audio = vec![0xFF; 256];
+        let out = env::temp_dir().join("musikr_appended_out.mp3");
+        std::fs::write(&out, &audio).unwrap();
+
+        let options = SaveOptions {
+            placement: Some(Placement::Append),
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        let data = std::fs::read(&out).unwrap();
+        assert_eq!(&data[..256], &audio[..]);
+        assert_eq!(&data[256..259], b"ID3");
+        assert_eq!(&data[data.len() - 10..data.len() - 7], b"3DI");
+        assert_eq!(data.len(), 256 + tag.size() as usize + 20);
+
+        // Saving the tag again should keep it in-place.
+        let mut new_tag = Tag::open(&out).unwrap();
+        assert_eq!(new_tag.placement(), Placement::Append);
+        assert_eq!(new_tag.size(), tag.size());
+        assert_eq!(
+            new_tag.frames["TIT2"].to_string(),
+            tag.frames["TIT2"].to_string()
+        );
+
+        new_tag.save(&out).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), data);
+
+        // Moving the tag should remove the old one.
+        let options = SaveOptions {
+            placement: Some(Placement::Prepend),
+            footer: Some(false),
+            ..Default::default()
+        };
+
+        new_tag.save_with(&out, &options).unwrap();
+
+        let data = std::fs::read(&out).unwrap();
+        assert_eq!(&data[..3], b"ID3");
+        assert_eq!(&data[data.len() - 256..], &audio[..]);
+        assert_eq!(Tag::open(&out).unwrap().placement(), Placement::Prepend);
+    }
+
+    #[test]
+    fn write_appended_tag_before_id3v1() {
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/itunes_sizes.mp3";
+        let mut tag = Tag::open(&path).unwrap();
+
+        let mut id3v1 = vec![0; 128];
+        id3v1[0..3].copy_from_slice(b"TAG");
+
+        let mut file = vec![0xFF; 256];
+        file.extend(&id3v1);
+
+        let out = env::temp_dir().join("musikr_appended_id3v1_out.mp3");
+        std::fs::write(&out, &file).unwrap();
+
+        let options = SaveOptions {
+            placement: Some(Placement::Append),
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        let data = std::fs::read(&out).unwrap();
+        assert_eq!(&data[data.len() - 128..], &id3v1[..]);
+        assert_eq!(&data[data.len() - 138..data.len() - 135], b"3DI");
+
+        let new_tag = Tag::open(&out).unwrap();
+        assert_eq!(new_tag.placement(), Placement::Append);
+        assert_eq!(
+            new_tag.frames["TIT2"].to_string(),
+            tag.frames["TIT2"].to_string()
+        );
+    }
+
+    #[test]
+    fn write_id3v23_never_appended() {
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/v22.mp3";
+        let mut tag = Tag::open(&path).unwrap();
+
+        let out = env::temp_dir().join("musikr_id3v23_append_out.mp3");
+        std::fs::write(&out, [0xFF; 256]).unwrap();
+
+        let options = SaveOptions {
+            placement: Some(Placement::Append),
+            footer: Some(true),
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        let data = std::fs::read(&out).unwrap();
+        assert_eq!(&data[..3], b"ID3");
+        assert_eq!(data[5] & 0x10, 0);
+        assert_eq!(tag.placement(), Placement::Prepend);
+    }
+
+    #[test]
+    fn write_crc32() {
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/v22.mp3";
+
+        for version in [SaveVersion::V23, SaveVersion::V24] {
+            let mut tag = Tag::open(&path).unwrap();
+            tag.update(version);
+            assert_eq!(tag.crc_matches(), None);
+
+            let out = env::temp_dir().join("musikr_crc32_out.mp3");
+            std::fs::write(&out, []).unwrap();
+
+            let options = SaveOptions {
+                crc32: true,
+                ..Default::default()
+            };
+
+            tag.save_with(&out, &options).unwrap();
+            assert_eq!(tag.crc_matches(), Some(true));
+
+            let new_tag = Tag::open(&out).unwrap();
+            let ext = new_tag.extended_header.as_ref().unwrap();
+            assert_eq!(ext.crc32, tag.extended_header.as_ref().unwrap().crc32);
+            assert_eq!(new_tag.crc_matches(), Some(true));
+            assert_eq!(new_tag.frames["TIT2"].to_string(), "cosmic american");
+
+            // Corrupting the tag body should cause the CRC-32 to fail.
+            let mut data = std::fs::read(&out).unwrap();
+            let pos = data
+                .windows(15)
+                .position(|w| w == b"cosmic american")
+                .unwrap();
+            data[pos] = b'C';
+            std::fs::write(&out, data).unwrap();
+
+            let new_tag = Tag::open(&out).unwrap();
+            assert_eq!(new_tag.crc_matches(), Some(false));
+        }
+    }
+
+    #[test]
+    fn write_seek_frames() {
+        use crate::id3v2::frames::{FrameId, PrivateFrame, SeekFrame, TextFrame};
+
+        let audio = vec![0x16; 256];
+        let out = env::temp_dir().join("musikr_seek_out.mp3");
+        std::fs::write(&out, &audio).unwrap();
+
+        let mut tag = Tag::new();
+        let mut tit2 = TextFrame::new(FrameId::new(b"TIT2"));
+        tit2.text = vec![String::from("Seek")];
+        tag.frames.add(tit2);
+        tag.frames.add(SeekFrame { offset: 128 });
+        tag.frames.add(AudioSeekIndexFrame {
+            start: 0,
+            len: 256,
+            bits: 8,
+            points: vec![0, 64, 128, 192],
+        });
+
+        // The index should point to the audio once the tag is prepended.
+        tag.save(&out).unwrap();
+
+        let aspi_start = |tag: &Tag| {
+            tag.frames["ASPI"]
+                .downcast::<AudioSeekIndexFrame>()
+                .unwrap()
+                .start
+        };
+
+        let data = std::fs::read(&out).unwrap();
+        let start = tag.size() + 10;
+        assert_eq!(aspi_start(&tag), start);
+        assert_eq!(&data[start as usize..], &audio[..]);
+
+        let mut new_tag = Tag::open(&out).unwrap();
+        assert_eq!(aspi_start(&new_tag), start);
+
+        // Growing the tag past its padding should move the index with the audio.
+        new_tag.frames.add(PrivateFrame {
+            owner: String::from("musikr"),
+            data: vec![0; 4096],
+        });
+
+        new_tag.save(&out).unwrap();
+
+        let data = std::fs::read(&out).unwrap();
+        let start = new_tag.size() + 10;
+        assert_eq!(aspi_start(&new_tag), start);
+        assert_eq!(&data[start as usize..], &audio[..]);
+        assert_eq!(aspi_start(&Tag::open(&out).unwrap()), start);
+        assert!(new_tag.frames.contains_key("SEEK"));
+
+        // Appending the tag should move the audio back to the start and invalidate the seek offset.
+        let options = SaveOptions {
+            placement: Some(Placement::Append),
+            ..Default::default()
+        };
+
+        new_tag.save_with(&out, &options).unwrap();
+        assert_eq!(aspi_start(&new_tag), 0);
+        assert!(!new_tag.frames.contains_key("SEEK"));
+
+        let new_tag = Tag::open(&out).unwrap();
+        assert_eq!(aspi_start(&new_tag), 0);
+        assert!(!new_tag.frames.contains_key("SEEK"));
+    }
+
+    #[test]
+    fn write_unsettled_seek_index() {
+        use crate::id3v2::tag::PaddingPolicy;
+        use std::cell::Cell;
+
+        let audio = vec![0x16; 256];
+        let out = env::temp_dir().join("musikr_unsettled_seek_out.mp3");
+        std::fs::write(&out, &audio).unwrap();
+
+        let mut tag = Tag::new();
+        tag.frames.add(AudioSeekIndexFrame {
+            start: 0,
+            len: 256,
+            bits: 8,
+            points: vec![0, 64, 128, 192],
+        });
+
+        // Padding that changes on every pass will never let the tag size settle.
+        let passes = Cell::new(0);
+
+        let custom = |_, _, _| {
+            passes.set(passes.get() + 1);
+            passes.get() * 16
+        };
+
+        let options = SaveOptions {
+            padding: PaddingPolicy::Custom(&custom),
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        // The index that was written should still match the one in memory.
+        let start = tag.frames["ASPI"]
+            .downcast::<AudioSeekIndexFrame>()
+            .unwrap()
+            .start;
+
+        let new_tag = Tag::open(&out).unwrap();
+        let new_start = new_tag.frames["ASPI"]
+            .downcast::<AudioSeekIndexFrame>()
+            .unwrap()
+            .start;
+
+        assert!(passes.get() > 4);
+        assert_eq!(new_start, start);
+    }
+
+    #[test]
+    fn write_grouped_frames() {
+        use crate::id3v2::frames::{FrameId, GroupIdFrame, TextFrame};
+
+        for version in [SaveVersion::V23, SaveVersion::V24] {
+            let out = env::temp_dir().join("musikr_grouped_out.mp3");
+            std::fs::write(&out, []).unwrap();
+
+            let mut tag = Tag::with_version(version);
+            let mut tit2 = TextFrame::new(FrameId::new(b"TIT2"));
+            tit2.encoding = Encoding::Latin1;
+            tit2.text = vec![String::from("Grouped")];
+            tag.frames.add(tit2);
+            tag.frames.add(GroupIdFrame {
+                owner: String::from("https://musikr.org"),
+                group: 0x80,
+                data: Vec::new(),
+            });
+
+            tag.frames.flags_mut("TIT2").unwrap().group = Some(0x80);
+            tag.save(&out).unwrap();
+
+            let mut new_tag = Tag::open(&out).unwrap();
+            let grid = new_tag.frames["GRID:https://musikr.org"]
+                .downcast::<GroupIdFrame>()
+                .unwrap();
+
+            assert_eq!(grid.group, 0x80);
+            assert_eq!(new_tag.frames["TIT2"].to_string(), "Grouped");
+            assert_eq!(new_tag.frames.flags("TIT2").unwrap().group, Some(0x80));
+            let grid_flags = new_tag.frames.flags("GRID:https://musikr.org").unwrap();
+            assert_eq!(grid_flags.group, None);
+
+            // Removing the group should also be preserved.
+            new_tag.frames.flags_mut("TIT2").unwrap().group = None;
+            new_tag.save(&out).unwrap();
+
+            let new_tag = Tag::open(&out).unwrap();
+            assert_eq!(new_tag.frames.flags("TIT2").unwrap().group, None);
+        }
+    }
+
+    #[test]
+    fn write_encrypted_frames() {
+        use crate::id3v2::crypto::FrameCipher;
+        use crate::id3v2::frames::{EncryptionRegistrationFrame, FrameId, TextFrame};
+        use crate::id3v2::tag::{CompressionPolicy, OpenOptions};
+
+        struct XorCipher;
+
+        impl FrameCipher for XorCipher {
+            fn decrypt(&self, owner: &str, method: u8, data: &[u8]) -> Option<Vec<u8>> {
+                assert_eq!(owner, "xor");
+                Some(data.iter().map(|b| b ^ method).collect())
+            }
+
+            fn encrypt(&self, owner: &str, method: u8, data: &[u8]) -> Option<Vec<u8>> {
+                self.decrypt(owner, method, data)
+            }
+        }
+
+        let compression = if cfg!(feature = "id3v2_compression") {
+            CompressionPolicy::All
+        } else {
+            CompressionPolicy::None
+        };
+
+        for version in [SaveVersion::V23, SaveVersion::V24] {
+            let out = env::temp_dir().join("musikr_encrypted_out.mp3");
+            std::fs::write(&out, []).unwrap();
+
+            let mut tag = Tag::with_version(version);
+            tag.frames.add(EncryptionRegistrationFrame {
+                owner: String::from("xor"),
+                method: 0x80,
+                data: Vec::new(),
+            });
+
+            let mut tit2 = TextFrame::new(FrameId::new(b"TIT2"));
+            tit2.encoding = Encoding::Latin1;
+            tit2.text = vec![String::from("Secret").repeat(16)];
+            tag.frames.add(tit2);
+
+            let flags = tag.frames.flags_mut("TIT2").unwrap();
+            flags.encryption = Some(0x80);
+            flags.group = Some(0x81);
+
+            let options = SaveOptions {
+                compression: compression.clone(),
+                cipher: Some(&XorCipher),
+                ..Default::default()
+            };
+
+            tag.save_with(&out, &options).unwrap();
+
+            let data = std::fs::read(&out).unwrap();
+            assert!(!data.windows(6).any(|w| w == b"Secret"));
+
+            // Without a cipher, the frame should be unknown.
+            let new_tag = Tag::open(&out).unwrap();
+            assert!(!new_tag.frames.contains_key("TIT2"));
+            assert_eq!(new_tag.unknown_frames.frames().len(), 1);
+
+            let options = OpenOptions {
+                cipher: Some(&XorCipher),
+                ..Default::default()
+            };
+
+            let mut new_tag =
+                Tag::open_with_options(&out, &DefaultFrameParser::default(), &options).unwrap();
+
+            assert_eq!(new_tag.frames["TIT2"].to_string(), "Secret".repeat(16));
+            assert!(new_tag.unknown_frames.frames().is_empty());
+
+            let flags = new_tag.frames.flags("TIT2").unwrap();
+            assert_eq!(flags.encryption, Some(0x80));
+            assert_eq!(flags.group, Some(0x81));
+
+            // Saving without a cipher should fail instead of writing the frame in the clear
+            // or dropping it.
+            let before = std::fs::read(&out).unwrap();
+            assert!(matches!(new_tag.save(&out), Err(SaveError::Unencrypted)));
+            assert_eq!(std::fs::read(&out).unwrap(), before);
+        }
+    }
+
+    #[test]
+    fn write_encrypted_chapter_frames() {
+        use crate::id3v2::frames::ChapterFrame;
+
+        let out = env::temp_dir().join("musikr_encrypted_chapter_out.mp3");
+        std::fs::write(&out, []).unwrap();
+
+        let mut chap = ChapterFrame {
+            element_id: String::from("chp1"),
+            ..Default::default()
+        };
+
+        chap.frames.add(crate::text_frame!(b"TIT2", ["Chapter 1"]));
+        chap.frames.flags_mut("TIT2").unwrap().encryption = Some(0x80);
+
+        let mut tag = Tag::new();
+        tag.frames.add(chap);
+
+        // Embedded frames can never be encrypted, so the save should fail instead of
+        // leaving the frame out.
+        assert!(matches!(tag.save(&out), Err(SaveError::Unencrypted)));
+        assert!(std::fs::read(&out).unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_signed_frames() {
+        use crate::id3v2::crypto::FrameSigner;
+        use crate::id3v2::frames::{FrameId, GroupIdFrame, SignatureFrame, TextFrame};
+        use crate::id3v2::tag::OpenOptions;
+
+        struct SumSigner;
+
+        impl FrameSigner for SumSigner {
+            fn sign(&self, owner: &str, group: u8, data: &[u8]) -> Option<Vec<u8>> {
+                assert_eq!(owner, "sum");
+                let sum = data
+                    .iter()
+                    .fold(group as u32, |acc, &b| acc.wrapping_add(b as u32));
+                Some(sum.to_be_bytes().to_vec())
+            }
+
+            fn verify(&self, owner: &str, group: u8, data: &[u8], signature: &[u8]) -> bool {
+                self.sign(owner, group, data).as_deref() == Some(signature)
+            }
+        }
+
+        let out = env::temp_dir().join("musikr_signed_out.mp3");
+        std::fs::write(&out, []).unwrap();
+
+        let mut tag = Tag::new();
+        tag.frames.add(GroupIdFrame {
+            owner: String::from("sum"),
+            group: 0x80,
+            data: Vec::new(),
+        });
+
+        let mut tit2 = TextFrame::new(FrameId::new(b"TIT2"));
+        tit2.text = vec![String::from("Signed")];
+        tag.frames.add(tit2);
+        tag.frames.flags_mut("TIT2").unwrap().group = Some(0x80);
+
+        let mut talb = TextFrame::new(FrameId::new(b"TALB"));
+        talb.text = vec![String::from("Unsigned")];
+        tag.frames.add(talb);
+
+        tag.frames.add(SignatureFrame {
+            group: 0x80,
+            signature: Vec::new(),
+        });
+
+        let flags = tag.frames.flags_mut("SIGN:128").unwrap();
+        flags.discard_on_tag_alter = true;
+
+        let options = SaveOptions {
+            signer: Some(&SumSigner),
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+        assert_eq!(tag.signatures_match(), Some(true));
+
+        let sign = tag.frames["SIGN:128"].downcast::<SignatureFrame>().unwrap();
+        assert_eq!(sign.signature.len(), 4);
+
+        // The flags of the signature should survive it being re-signed.
+        let flags = tag.frames.flags("SIGN:128").unwrap();
+        assert!(flags.discard_on_tag_alter);
+
+        let flags = Tag::open(&out).unwrap().frames.flags("SIGN:128").unwrap();
+        assert!(flags.discard_on_tag_alter);
+
+        let options = OpenOptions {
+            signer: Some(&SumSigner),
+            ..Default::default()
+        };
+
+        let open = |path: &Path| {
+            Tag::open_with_options(path, &DefaultFrameParser::default(), &options).unwrap()
+        };
+
+        assert_eq!(Tag::open(&out).unwrap().signatures_match(), None);
+        assert_eq!(open(&out).signatures_match(), Some(true));
+
+        // Frames outside of the group can be changed without invalidating the signature.
+        let mut new_tag = Tag::open(&out).unwrap();
+        new_tag.frames.remove("TALB");
+        new_tag.save(&out).unwrap();
+        assert_eq!(new_tag.signatures_match(), None);
+        assert_eq!(open(&out).signatures_match(), Some(true));
+
+        // But tampering with a signed frame should be caught.
+        let mut data = std::fs::read(&out).unwrap();
+        let pos = data.windows(6).position(|w| w == b"Signed").unwrap();
+        data[pos..pos + 6].copy_from_slice(b"Forged");
+        std::fs::write(&out, data).unwrap();
+
+        let new_tag = open(&out);
+        assert_eq!(new_tag.frames["TIT2"].to_string(), "Forged");
+        assert_eq!(new_tag.signatures_match(), Some(false));
+    }
+
+    #[test]
+    fn write_status_flags() {
+        use crate::id3v2::frames::{FrameId, TextFrame};
+
+        let out = env::temp_dir().join("musikr_status_out.mp3");
+        std::fs::write(&out, []).unwrap();
+
+        for version in [SaveVersion::V23, SaveVersion::V24] {
+            let mut tag = Tag::with_version(version);
+
+            for (id, text) in [(b"TIT2", "Read-only"), (b"TALB", "Audio-bound")] {
+                let mut frame = TextFrame::new(FrameId::new(id));
+                frame.encoding = Encoding::Latin1;
+                frame.text = vec![String::from(text)];
+                tag.frames.add(frame);
+            }
+
+            tag.frames.flags_mut("TIT2").unwrap().read_only = true;
+            tag.frames.flags_mut("TALB").unwrap().discard_on_file_alter = true;
+            tag.save(&out).unwrap();
+
+            let mut new_tag = Tag::open(&out).unwrap();
+
+            let tit2 = new_tag.frames.flags("TIT2").unwrap();
+            assert!(tit2.read_only);
+            assert!(!tit2.discard_on_file_alter);
+
+            let talb = new_tag.frames.flags("TALB").unwrap();
+            assert!(!talb.read_only);
+            assert!(talb.discard_on_file_alter);
+
+            // Edits to read-only frames should be refused, unless explicitly allowed.
+            new_tag.frames["TIT2"]
+                .downcast_mut::<TextFrame>()
+                .unwrap()
+                .text = vec![String::from("Edited")];
+
+            assert!(matches!(new_tag.save(&out), Err(SaveError::ReadOnly)));
+
+            let options = SaveOptions {
+                edit_read_only: true,
+                ..Default::default()
+            };
+
+            new_tag.save_with(&out, &options).unwrap();
+
+            // The new contents should now be considered read-only.
+            new_tag.frames.remove("TIT2");
+            assert!(matches!(new_tag.save(&out), Err(SaveError::ReadOnly)));
+
+            let mut new_tag = Tag::open(&out).unwrap();
+            assert_eq!(new_tag.frames["TIT2"].to_string(), "Edited");
+
+            // Altering the file should drop TALB.
+            let options = SaveOptions {
+                file_altered: true,
+                ..Default::default()
+            };
+
+            new_tag.save_with(&out, &options).unwrap();
+            assert!(!new_tag.frames.contains_key("TALB"));
+            assert!(!Tag::open(&out).unwrap().frames.contains_key("TALB"));
+        }
+    }
+
+    #[test]
+    fn write_restricted_read_only_frames() {
+        use crate::id3v2::frames::{FrameId, TextFrame};
+        use crate::id3v2::tag::{
+            ImageEncodingRestriction, ImageSizeRestriction, Restrictions, TagSizeRestriction,
+            TextEncodingRestriction, TextSizeRestriction,
+        };
+
+        let out = env::temp_dir().join("musikr_restricted_read_only_out.mp3");
+        std::fs::write(&out, []).unwrap();
+
+        let mut tag = Tag::new();
+        let mut tit2 = TextFrame::new(FrameId::new(b"TIT2"));
+        tit2.encoding = Encoding::Utf16;
+        tit2.text = vec![String::from("Read-only")];
+        tag.frames.add(tit2);
+        tag.frames.flags_mut("TIT2").unwrap().read_only = true;
+        tag.save(&out).unwrap();
+
+        // Restrictions that would re-encode a read-only frame should be refused.
+        tag.extended_header = Some(ExtendedHeader {
+            restrictions: Some(Restrictions {
+                tag_size: TagSizeRestriction::Max128Frames1Mb,
+                text_encoding: TextEncodingRestriction::Latin1OrUtf8,
+                text_size: TextSizeRestriction::None,
+                image_encoding: ImageEncodingRestriction::None,
+                image_size: ImageSizeRestriction::None,
+            }),
+            ..Default::default()
+        });
+
+        let options = SaveOptions {
+            restrict: true,
+            ..Default::default()
+        };
+
+        let data = std::fs::read(&out).unwrap();
+        assert!(matches!(
+            tag.save_with(&out, &options),
+            Err(SaveError::ReadOnly)
+        ));
+        assert_eq!(std::fs::read(&out).unwrap(), data);
+
+        let tit2 = tag.frames["TIT2"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tit2.encoding, Encoding::Utf16);
+    }
+
+    #[test]
+    fn write_compatible_tag() {
+        use crate::id3v2::frames::{FrameId, TextFrame};
+
+        let out = env::temp_dir().join("musikr_compatible_out.mp3");
+        std::fs::write(&out, []).unwrap();
+
+        let mut tag = Tag::with_version(SaveVersion::V23);
+
+        for (id, text) in [(b"TSOP", "Artist, The"), (b"TMOO", "Calm")] {
+            let mut frame = TextFrame::new(FrameId::new(id));
+            frame.encoding = Encoding::Latin1;
+            frame.text = vec![String::from(text)];
+            tag.frames.add(frame);
+        }
+
+        let options = SaveOptions {
+            compatibility: CompatibilityPolicy::Compatible,
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        let mut tag = Tag::open(&out).unwrap();
+        assert_eq!(tag.version(), Version::V23);
+        assert_eq!(tag.frames["TSOP"].to_string(), "Artist, The");
+        assert_eq!(tag.frames["TXXX:MOOD"].to_string(), "Calm");
+        assert!(!tag.frames.contains_key("TMOO"));
+
+        // A strict save should drop TSOP.
+        tag.save(&out).unwrap();
+        assert!(!Tag::open(&out).unwrap().frames.contains_key("TSOP"));
+    }
+
+    #[test]
+    fn write_migrated_unknown_frames() {
+        use std::io::Cursor;
+
+        const DATA: &[u8] = b"ID3\x03\x00\x00\x00\x00\x00\x1E\
+                              NCON\x00\x00\x00\x05\x20\x20\x80data\
+                              ENCX\x00\x00\x00\x05\x00\x40\x80data";
+
+        let mut cursor = Cursor::new(DATA.to_vec());
+        let mut tag = Tag::read_from(&mut cursor).unwrap();
+        let unknowns = tag.unknown_frames.frames();
+
+        // The group symbol should be kept in front of the data of grouped frames.
+        assert_eq!(unknowns.len(), 2);
+        assert_eq!(unknowns[0].flags(), 0x2020);
+        assert_eq!(unknowns[0].data(), b"\x80data");
+
+        tag.update(SaveVersion::V24);
+        tag.write_to(&mut cursor, &SaveOptions::default()).unwrap();
+
+        // The encrypted frame cannot be moved to ID3v2.4.
+        let tag = Tag::read_from(&mut cursor).unwrap();
+        let unknowns = tag.unknown_frames.frames();
+
+        assert_eq!(tag.version(), Version::V24);
+        assert_eq!(unknowns.len(), 1);
+        assert_eq!(unknowns[0].id(), b"NCON");
+        assert_eq!(unknowns[0].flags(), 0x1040);
+        assert_eq!(unknowns[0].data(), b"\x80data");
+    }
+
+    #[test]
+    fn write_read_only_genres() {
+        use std::io::Cursor;
+
+        const DATA: &[u8] = b"ID3\x03\x00\x00\x00\x00\x00\x13\
+                              TCON\x00\x00\x00\x09\x20\x00\x00(17)Rock";
+
+        // Saving without changing the version should not rewrite the genres, which would
+        // also fail due to the frame being read-only.
+        let mut cursor = Cursor::new(DATA.to_vec());
+        let mut tag = Tag::read_from(&mut cursor).unwrap();
+        tag.write_to(&mut cursor, &SaveOptions::default()).unwrap();
+
+        let tag = Tag::read_from(&mut cursor).unwrap();
+        assert_eq!(tag.version(), Version::V23);
+        assert_eq!(tag.frames["TCON"].to_string(), "(17)Rock");
+        assert!(tag.frames.flags("TCON").unwrap().read_only);
+    }
+
+    #[test]
+    fn write_restricted_tag() {
+        use crate::id3v2::frames::{AttachedPictureFrame, PrivateFrame, TextFrame};
+        use crate::id3v2::tag::{
+            ImageEncodingRestriction, ImageSizeRestriction, Restrictions, TagSizeRestriction,
+            TextEncodingRestriction, TextSizeRestriction,
+        };
+
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/v22.mp3";
+        let mut tag = Tag::open(&path).unwrap();
+        tag.update(SaveVersion::V24);
+
+        tag.extended_header = Some(ExtendedHeader {
+            restrictions: Some(Restrictions {
+                tag_size: TagSizeRestriction::Max32Frames4Kb,
+                text_encoding: TextEncodingRestriction::Latin1OrUtf8,
+                text_size: TextSizeRestriction::LessThan128Chars,
+                image_encoding: ImageEncodingRestriction::OnlyPngOrJpeg,
+                image_size: ImageSizeRestriction::None,
+            }),
+            ..Default::default()
+        });
+
+        let mut tit2 = TextFrame::new(frames::FrameId::new(b"TIT2"));
+        tit2.encoding = Encoding::Utf16;
+        tit2.text = vec![String::from("cosmic american")];
+        tag.frames.insert(tit2);
+
+        tag.frames.add(AttachedPictureFrame {
+            mime: String::from("image/bmp"),
+            picture: vec![0; 128],
+            ..Default::default()
+        });
+
+        // This frame leaves too little room for the default padding.
+        tag.frames.add(PrivateFrame {
+            owner: String::from("musikr"),
+            data: vec![0x16; 3000],
+        });
+
+        let out = env::temp_dir().join("musikr_restricted_out.mp3");
+        std::fs::write(&out, [0xFF; 2048]).unwrap();
+
+        let options = SaveOptions {
+            restrict: true,
+            ..Default::default()
+        };
+
+        let report = tag.save_with(&out, &options).unwrap();
+
+        assert!(report
+            .restrictions
+            .contains(&RestrictionChange::RemovedPicture {
+                key: String::from("APIC:"),
+                mime: String::from("image/bmp")
+            }));
+
+        assert!(report.restrictions.contains(&RestrictionChange::Reencoded {
+            key: String::from("TIT2"),
+            from: Encoding::Utf16,
+            to: Encoding::Utf8
+        }));
+
+        assert!(matches!(
+            report.restrictions.last(),
+            Some(RestrictionChange::ReducedPadding { from: 1024, .. })
+        ));
+
+        assert_eq!(tag.size() + 10, 4 * 1024);
+
+        let new_tag = Tag::open(&out).unwrap();
+        assert!(!new_tag.frames.contains_key("APIC:"));
+        assert_eq!(new_tag.frames["TIT2"].to_string(), "cosmic american");
+
+        // Large tags can't be made to fit, and should not be written at all.
+        tag.frames.add(AttachedPictureFrame {
+            mime: String::from("image/png"),
+            picture: vec![0; 4096],
+            ..Default::default()
+        });
+
+        let data = std::fs::read(&out).unwrap();
+
+        let mut tit2 = TextFrame::new(frames::FrameId::new(b"TIT2"));
+        tit2.encoding = Encoding::Utf16;
+        tit2.text = vec![String::from("cosmic american")];
+        tag.frames.insert(tit2);
+
+        assert!(matches!(
+            tag.save_with(&out, &options),
+            Err(SaveError::Restricted)
+        ));
+
+        assert_eq!(std::fs::read(&out).unwrap(), data);
+
+        // The tag should be left as it was before the failed save.
+        let tit2 = tag.frames["TIT2"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tit2.encoding, Encoding::Utf16);
+    }
+
     fn id3v22_ensure(tag: &Tag, version: Version) {
         assert_eq!(tag.version(), version);
         assert_eq!(tag.frames["TIT2"].to_string(), "cosmic american");
@@ -1,8 +1,13 @@
 //! Frame collection and management.
 
-use crate::id3v2::frames::{self, CreditsFrame, Frame, TextFrame, UnknownFrame, UserTextFrame};
-use crate::id3v2::tag::{TagHeader, Version};
-use log::{info, warn};
+use crate::id3v2::crypto::{Encryptor, FrameCipher};
+use crate::id3v2::frames::{
+    self, ChapterFrame, CreditsFrame, Frame, FrameFlags, FrameId, TableOfContentsFrame, TextFrame,
+    UnknownFrame, UserTextFrame,
+};
+use crate::id3v2::tag::{CompressionPolicy, TagHeader, Version};
+use crate::id3v2::{SaveError, SaveResult};
+use log::{error, info, warn};
 use std::cmp::Ordering;
 use std::collections::btree_map::{
     BTreeMap, Entry, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys,
@@ -20,6 +25,10 @@ use std::ops::{Deref, DerefMut, Index, IndexMut};
 /// `TRCK`, `TPOS`, `TDRC`, and `TCON` will be ordered first, with all other frames being ordered
 /// by size and then key.
 ///
+/// Each frame can also have [`FrameFlags`](crate::id3v2::frames::FrameFlags) associated with
+/// it, which are accessed with [`flags`](FrameMap::flags) and [`flags_mut`](FrameMap::flags_mut).
+/// These are tied to the key of the frame, so they will be lost if the key of a frame changes.
+///
 /// # Example
 ///
 /// ```
@@ -49,6 +58,7 @@ use std::ops::{Deref, DerefMut, Index, IndexMut};
 #[derive(Debug, Clone, Default)]
 pub struct FrameMap {
     map: BTreeMap<String, Box<dyn Frame>>,
+    flags: BTreeMap<String, FrameFlags>,
 }
 
 impl FrameMap {
@@ -114,17 +124,38 @@ impl FrameMap {
             }
 
             Entry::Vacant(entry) => {
+                // Make sure that this frame does not inherit the flags of a frame whose key
+                // has since changed.
+                self.flags.remove(entry.key());
                 entry.insert(frame);
             }
         }
     }
 
+    /// Adds a boxed frame alongside its flags. The flags are only kept if the frame
+    /// was not merged into a pre-existing frame.
+    pub(crate) fn add_with_flags(&mut self, frame: Box<dyn Frame>, flags: FrameFlags) {
+        let key = frame.key();
+        let vacant = !self.map.contains_key(&key);
+
+        self.add_boxed(frame);
+
+        if vacant && flags != FrameFlags::default() {
+            self.flags.insert(key, flags);
+        }
+    }
+
     /// Inserts a boxed frame into the `FrameMap`.
     ///
     /// If a frame with the same key is not present in the `FrameMap`, then the frame
     /// will be added. If a frame is present, then it will be overwritten.
+    ///
+    /// Any [`FrameFlags`](crate::id3v2::frames::FrameFlags) of the overwritten frame
+    /// will be reset.
     pub fn insert_boxed(&mut self, frame: Box<dyn Frame>) {
-        self.map.insert(frame.key(), frame);
+        let key = frame.key();
+        self.flags.remove(&key);
+        self.map.insert(key, frame);
     }
 
     /// Returns a reference to the frame corresponding to the key.
@@ -137,6 +168,26 @@ impl FrameMap {
         Some(self.map.get_mut(key)?.deref_mut())
     }
 
+    /// Returns the [`FrameFlags`](crate::id3v2::frames::FrameFlags) of the frame corresponding
+    /// to the key, or `None` if there is no such frame.
+    pub fn flags(&self, key: &str) -> Option<FrameFlags> {
+        if !self.map.contains_key(key) {
+            return None;
+        }
+
+        Some(self.flags.get(key).copied().unwrap_or_default())
+    }
+
+    /// Returns a mutable reference to the [`FrameFlags`](crate::id3v2::frames::FrameFlags)
+    /// of the frame corresponding to the key, or `None` if there is no such frame.
+    pub fn flags_mut(&mut self, key: &str) -> Option<&mut FrameFlags> {
+        if !self.map.contains_key(key) {
+            return None;
+        }
+
+        Some(self.flags.entry(key.to_string()).or_default())
+    }
+
     /// Returns a list of references to all frames that have the specified Frame ID.
     ///
     /// This method does not require the `id` to be valid. If validity is desired,
@@ -177,6 +228,26 @@ impl FrameMap {
         keys.iter().map(|key| self.remove(key).unwrap()).collect()
     }
 
+    /// Removes all frames that match the specified Frame ID alongside their flags, so that
+    /// they can be re-added with [`add_with_flags`](FrameMap::add_with_flags).
+    pub(crate) fn remove_all_with_flags(
+        &mut self,
+        id: &[u8; 4],
+    ) -> Vec<(Box<dyn Frame>, FrameFlags)> {
+        let keys: Vec<String> = self
+            .iter()
+            .filter(|(_, frame)| frame.id() == id)
+            .map(|(key, _)| key.to_string())
+            .collect();
+
+        keys.iter()
+            .map(|key| {
+                let flags = self.flags.get(key).copied().unwrap_or_default();
+                (self.remove(key).unwrap(), flags)
+            })
+            .collect()
+    }
+
     /// Returns true if the map contains a value for the specified key.
     pub fn contains_key(&self, key: &str) -> bool {
         self.map.contains_key(key)
@@ -210,7 +281,23 @@ impl FrameMap {
     where
         F: FnMut(&String, &mut dyn Frame) -> bool,
     {
-        self.map.retain(|k, v| keep(k, v.deref_mut()))
+        self.map.retain(|k, v| keep(k, v.deref_mut()));
+
+        let map = &self.map;
+        self.flags.retain(|k, _| map.contains_key(k));
+    }
+
+    /// Clears the map, removing all elements.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.flags.clear();
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    pub fn remove(&mut self, key: &str) -> Option<Box<dyn Frame>> {
+        self.flags.remove(key);
+        self.map.remove(key)
     }
 
     /// Returns a reference to the inner [`BTreeMap`](std::collections::BTreeMap) for this instance.
@@ -218,17 +305,75 @@ impl FrameMap {
         &self.map
     }
 
-    pub(crate) fn render(&self, header: &TagHeader) -> impl Iterator<Item = u8> + '_ {
+    /// Renders the frames embedded in another frame. These are never compressed or encrypted.
+    /// Saving fails before this point if any of them are marked as encrypted, so they are only
+    /// left out here when the embedding frame is rendered on its own.
+    pub(crate) fn render_embedded(&self, header: &TagHeader) -> Vec<u8> {
+        let mut frames = self.clone();
+
+        for (key, flags) in &self.flags {
+            if flags.encryption.is_some() {
+                warn!("cannot encrypt embedded frame {}, leaving it out", key);
+                frames.remove(key);
+            }
+        }
+
+        match frames.render(header, &CompressionPolicy::None, None) {
+            Ok(data) => data,
+            Err(_) => unreachable!("no encrypted frames are left to render"),
+        }
+    }
+
+    /// Returns whether any frame embedded in a CHAP or CTOC frame is marked as encrypted.
+    /// There is no way to encrypt these, so the tag cannot be saved.
+    fn has_encrypted_embeds(&self) -> bool {
+        self.values().any(|frame| {
+            let embedded = match frame.downcast::<ChapterFrame>() {
+                Some(chap) => &chap.frames,
+                None => match frame.downcast::<TableOfContentsFrame>() {
+                    Some(ctoc) => &ctoc.frames,
+                    None => return false,
+                },
+            };
+
+            embedded
+                .flags
+                .values()
+                .any(|flags| flags.encryption.is_some())
+                || embedded.has_encrypted_embeds()
+        })
+    }
+
+    pub(crate) fn render(
+        &self,
+        header: &TagHeader,
+        compression: &CompressionPolicy,
+        cipher: Option<&dyn FrameCipher>,
+    ) -> SaveResult<Vec<u8>> {
         const PRIORITY: &[&[u8; 4]] = &[
             b"TIT2", b"TPE1", b"TALB", b"TRCK", b"TPOS", b"TDRC", b"TCON",
         ];
 
+        if self.has_encrypted_embeds() {
+            error!("frames embedded in CHAP or CTOC frames cannot be encrypted");
+            return Err(SaveError::Unencrypted);
+        }
+
+        let encryptor = cipher.map(|cipher| Encryptor::new(cipher, self));
         let mut frame_pairs: Vec<(&dyn Frame, Vec<u8>)> = Vec::new();
 
-        for frame in self.values() {
+        for (key, frame) in self.iter() {
             if !frame.is_empty() {
-                match frames::render(header, frame.deref()) {
+                // Flags are tied to the key of a frame, so they don't apply if it has changed.
+                let flags = match self.flags.get(key) {
+                    Some(flags) if key == frame.key() => *flags,
+                    _ => FrameFlags::default(),
+                };
+
+                match frames::render(header, frame, &flags, compression, encryptor.as_ref()) {
                     Ok(data) => frame_pairs.push((frame, data)),
+                    // Writing an encrypted frame in the clear would leak its contents.
+                    Err(SaveError::Unencrypted) => return Err(SaveError::Unencrypted),
                     Err(_) => warn!("could not render frame {}", frame.key()),
                 }
             } else {
@@ -259,17 +404,16 @@ impl FrameMap {
             }
         });
 
-        frame_pairs
+        Ok(frame_pairs
             .into_iter()
             .flat_map(|(_, data)| data.into_iter())
+            .collect())
     }
 
     delegate::delegate! {
         to self.map {
-            /// Clears the map, removing all elements.
-            pub fn clear(&mut self);
             /// Gets an iterator over the keys of the map, in sorted order.
-            pub fn keys(&self) -> Keys<String, Box<dyn Frame>>;
+            pub fn keys(&self) -> Keys<'_, String, Box<dyn Frame>>;
             /// Returns the number of elements in the map.
             pub fn len(&self) -> usize;
             /// Returns true if the map contains no elements.
@@ -282,9 +426,6 @@ impl FrameMap {
             /// The map cannot be used after calling this. The iterator element type is
             /// the frame instances.
             pub fn into_values(self) -> IntoValues<String, Box<dyn Frame>>;
-            /// Removes a key from the map, returning the value at the key if the key
-            /// was previously in the map.
-            pub fn remove(&mut self, key: &str) -> Option<Box<dyn Frame>>;
         }
     }
 }
@@ -332,13 +473,19 @@ impl<'a> IntoIterator for &'a mut FrameMap {
 
 impl Extend<(String, Box<dyn Frame>)> for FrameMap {
     fn extend<I: IntoIterator<Item = (String, Box<dyn Frame>)>>(&mut self, iterable: I) {
-        self.map.extend(iterable)
+        for (key, frame) in iterable {
+            self.flags.remove(&key);
+            self.map.insert(key, frame);
+        }
     }
 }
 
 impl From<BTreeMap<String, Box<dyn Frame>>> for FrameMap {
     fn from(other: BTreeMap<String, Box<dyn Frame>>) -> Self {
-        Self { map: other }
+        Self {
+            map: other,
+            flags: BTreeMap::new(),
+        }
     }
 }
 
@@ -350,8 +497,8 @@ fn is_both<T: Frame>(orig: &mut dyn Frame, new: &dyn Frame) -> bool {
 /// A collection of unknown frames.
 ///
 /// This collection is immutable and tied to the [`Version`](crate::id3v2::tag::Version)
-/// of the tag. If the tag is upgraded or downgraded at any point, then the frames in this
-/// will not be written.
+/// of the tag. If the tag is upgraded or downgraded at any point, then only the frames
+/// whose layout does not depend on the version will be written.
 #[derive(Debug, Clone)]
 pub struct UnknownFrames {
     version: Version,
@@ -374,4 +521,27 @@ impl UnknownFrames {
     pub fn frames(&self) -> &[UnknownFrame] {
         &self.frames
     }
+
+    /// Returns the frames in this collection that can be written to a tag of `version`,
+    /// converting them if needed.
+    pub(crate) fn migrate(
+        &self,
+        version: Version,
+        legacy_ids: &[([u8; 3], FrameId)],
+    ) -> Vec<UnknownFrame> {
+        let mut frames = Vec::new();
+
+        for frame in &self.frames {
+            match frame.migrate(self.version, version, legacy_ids) {
+                Some(frame) => frames.push(frame),
+                None => info!(
+                    "dropping unknown frame {}, as it cannot be written to {}",
+                    frame.id_str(),
+                    version
+                ),
+            }
+        }
+
+        frames
+    }
 }
@@ -1,9 +1,12 @@
+use crate::id3v2::frames::audio::{v23, v24};
+use crate::id3v2::frames::text::{Genre, Timestamp};
 use crate::id3v2::frames::{
-    self, ChapterFrame, CreditsFrame, Frame, FrameId, TableOfContentsFrame, TextFrame,
+    self, ChapterFrame, CreditsFrame, EqualizationFrame, EqualizationFrame2, Frame, FrameId,
+    RelativeVolumeFrame, RelativeVolumeFrame2, TableOfContentsFrame, TextFrame, UserTextFrame,
 };
+use crate::id3v2::tag::{CompatibilityPolicy, SaveVersion, UpdateReport};
 use crate::id3v2::{FrameMap, ParseError, ParseResult};
-use log::info;
-use std::str::Chars;
+use log::{info, warn};
 
 static V2_V3_CONV: &[(&[u8; 3], &[u8; 4])] = &[
     (b"BUF", b"RBUF"), // Recommended buffer size
@@ -86,8 +89,28 @@ static V3_UNSUPPORTED: &[&[u8; 4]] = &[
     b"TSST", b"TSOA", b"TSOP", b"TSOT",
 ];
 
+// Frames that are not in the ID3v2.3 spec, but that iTunes writes to ID3v2.3 tags anyway.
+// foobar2000, Mp3tag, and most other software understand them.
+static V3_DE_FACTO: &[&[u8; 4]] = &[b"TSOA", b"TSOP", b"TSOT"];
+
+// ID3v2.4 text frames and the TXXX descriptions they become in ID3v2.3 tags, named after the
+// fields that Mp3tag uses.
+static V3_USER_TEXT: &[(&[u8; 4], &str)] = &[
+    (b"TDEN", "ENCODINGTIME"),
+    (b"TDRL", "RELEASETIME"),
+    (b"TDTG", "TAGGINGTIME"),
+    (b"TMOO", "MOOD"),
+    (b"TPRO", "PRODUCEDNOTICE"),
+    (b"TSST", "SETSUBTITLE"),
+];
+
 static V4_UNSUPPORTED: &[&[u8; 4]] = &[b"EQUA", b"RVAD", b"TSIZ", b"TRDA"];
 
+// The ID3v2.3 spec never says what unit the RVAD/EQUA volumes are in. We assume that they are
+// 16-bit fixed-point changes in linear amplitude, so that an increment of 32768 doubles the
+// volume and a decrement of 32768 silences it. RVAD peaks are assumed to be 16-bit samples.
+const V3_UNITY: f64 = 32768.0;
+
 pub fn upgrade_v2_id(id: &[u8; 3]) -> ParseResult<FrameId> {
     // Walk the list of pairs until an ID matches
     for (v2_id, v3_id) in V2_V3_CONV {
@@ -99,23 +122,19 @@ pub fn upgrade_v2_id(id: &[u8; 3]) -> ParseResult<FrameId> {
     Err(ParseError::NotFound)
 }
 
-pub fn to_v3(frames: &mut FrameMap) {
+pub fn to_v3(frames: &mut FrameMap, policy: CompatibilityPolicy, report: &mut UpdateReport) {
     // Convert the TDRC frame into it's ID3v2.3 counterparts.
     if let Some(frame) = frames.remove("TDRC") {
-        from_tdrc(frame.downcast::<TextFrame>().unwrap(), frames)
+        from_tdrc(frame.downcast::<TextFrame>().unwrap(), frames, report)
     }
 
-    // Turn TDOR back into TORY. It's a bit more difficult here since we have to deal with
-    // the timestamp.
+    // Turn TDOR back into TORY, which only keeps the year of the timestamp.
     if let Some(frame) = frames.remove("TDOR") {
         let tdor = frame.downcast::<TextFrame>().unwrap();
         let mut tory = TextFrame::new(FrameId::new(b"TORY"));
 
-        for timestamp in &tdor.text {
-            if let Some(yyyy) = parse_timestamp(&mut timestamp.chars(), '-') {
-                // Like parse_year, tolerate years that aren't four chars.
-                tory.text.push(format!["{:0>4}", yyyy])
-            }
+        for stamp in parse_timestamps(tdor, report) {
+            tory.text.push(stamp.to_v3().0)
         }
 
         info!("downgraded TDOR to TORY: {}", tory);
@@ -155,10 +174,50 @@ pub fn to_v3(frames: &mut FrameMap) {
         (None, None) => {}
     }
 
+    // ID3v2.3 only allows one RVAD and EQUA frame, so we have to pick one of the RVA2/EQU2
+    // frames to convert. The rest are dropped.
+    if let Some(frame) = pick_adjustment(frames, b"RVA2").and_then(|key| frames.remove(&key)) {
+        let rvad = to_rvad(frame.downcast::<RelativeVolumeFrame2>().unwrap());
+        info!("downgraded RVA2 to RVAD: {}", rvad);
+
+        frames.add(rvad)
+    }
+
+    if let Some(frame) = pick_adjustment(frames, b"EQU2").and_then(|key| frames.remove(&key)) {
+        let equa = to_equa(frame.downcast::<EqualizationFrame2>().unwrap());
+        info!("downgraded EQU2 to EQUA: {}", equa);
+
+        frames.add(equa)
+    }
+
+    // Compatible tags keep the de-facto frames and preserve the other text frames as TXXX
+    // frames, instead of throwing the data away.
+    let compatible = policy == CompatibilityPolicy::Compatible;
+
+    if compatible {
+        for (id, desc) in V3_USER_TEXT {
+            for frame in frames.remove_all(id) {
+                if let Ok(text) = frames::downcast_box::<TextFrame>(frame) {
+                    info!("downgrading {} to TXXX:{}", text.id(), desc);
+
+                    frames.add(UserTextFrame {
+                        encoding: text.encoding,
+                        desc: String::from(*desc),
+                        text: text.text,
+                    })
+                }
+            }
+        }
+    }
+
     // Drop the remaining frames with no analogue.
     frames.retain(|_, frame| {
-        if V3_UNSUPPORTED.contains(&frame.id().as_ref()) {
-            info!("dropping ID3v2.3-incompatible frame {}", frame.id());
+        let id = frame.id();
+
+        if compatible && V3_DE_FACTO.contains(&id.as_ref()) {
+            true
+        } else if V3_UNSUPPORTED.contains(&id.as_ref()) {
+            info!("dropping ID3v2.3-incompatible frame {}", id);
             false
         } else {
             true
@@ -169,18 +228,18 @@ pub fn to_v3(frames: &mut FrameMap) {
 
     for frame in frames.get_all_mut(b"CHAP") {
         let chap = frame.downcast_mut::<ChapterFrame>().unwrap();
-        to_v3(&mut chap.frames);
+        to_v3(&mut chap.frames, policy, report);
     }
 
     for frame in frames.get_all_mut(b"CTOC") {
         let ctoc = frame.downcast_mut::<TableOfContentsFrame>().unwrap();
-        to_v3(&mut ctoc.frames);
+        to_v3(&mut ctoc.frames, policy, report);
     }
 }
 
-pub fn to_v4(frames: &mut FrameMap) {
+pub fn to_v4(frames: &mut FrameMap, report: &mut UpdateReport) {
     // Convert time frames into a single TDRC frame.
-    let tdrc = to_tdrc(frames);
+    let tdrc = to_tdrc(frames, report);
 
     if !tdrc.is_empty() {
         info!("upgraded to TDRC: {}", tdrc);
@@ -195,7 +254,9 @@ pub fn to_v4(frames: &mut FrameMap) {
         let mut tdor = TextFrame::new(FrameId::new(b"TDOR"));
 
         for year in &tory.text {
-            tdor.text.push(format!["{:0>4}", year])
+            if let Some((_, stamp)) = parse_year(tory.id(), year, report) {
+                tdor.text.push(stamp.to_string())
+            }
         }
 
         info!("upgraded TORY to TDOR: {}", tdor);
@@ -203,13 +264,28 @@ pub fn to_v4(frames: &mut FrameMap) {
         frames.add(tdor)
     }
 
-    // Like TORY, also pop off IPLS and re-add it with a new id.
-    if let Some(mut frame) = frames.remove("IPLS") {
-        info!("upgrading IPLS to TIPL");
+    // IPLS is already stored under the TIPL key, so it only needs a new id.
+    if let Some(ipls) = frames
+        .get_mut("TIPL")
+        .and_then(|frame| frame.downcast_mut::<CreditsFrame>())
+    {
+        if ipls.id() == b"IPLS" {
+            info!("upgrading IPLS to TIPL");
+            *ipls.id_mut() = FrameId::new(b"TIPL");
+        }
+    }
+
+    // RVAD and EQUA become RVA2 and EQU2 frames with no description.
+    if let Some(frame) = frames.remove("RVAD") {
+        info!("upgrading RVAD to RVA2");
 
-        let ipls = frame.downcast_mut::<CreditsFrame>().unwrap();
-        *ipls.id_mut() = FrameId::new(b"TIPL");
-        frames.add_boxed(frame);
+        frames.add(to_rva2(frame.downcast::<RelativeVolumeFrame>().unwrap()))
+    }
+
+    if let Some(frame) = frames.remove("EQUA") {
+        info!("upgrading EQUA to EQU2");
+
+        frames.add(to_equ2(frame.downcast::<EqualizationFrame>().unwrap()))
     }
 
     // Clear out all the frames that can't be upgraded.
@@ -226,20 +302,20 @@ pub fn to_v4(frames: &mut FrameMap) {
 
     for frame in frames.get_all_mut(b"CHAP") {
         let chap = frame.downcast_mut::<ChapterFrame>().unwrap();
-        to_v4(&mut chap.frames);
+        to_v4(&mut chap.frames, report);
     }
 
     for frame in frames.get_all_mut(b"CTOC") {
         let ctoc = frame.downcast_mut::<TableOfContentsFrame>().unwrap();
-        to_v4(&mut ctoc.frames);
+        to_v4(&mut ctoc.frames, report);
     }
 }
 
-fn to_tdrc(frames: &mut FrameMap) -> TextFrame {
+fn to_tdrc(frames: &mut FrameMap, report: &mut UpdateReport) -> TextFrame {
     // Turning the many ID3v2.3 date frames into TDRC mostly involves splicing
     // the required fields into the unified "yyyy-MM-ddTHH:mm:ss" timestamp.
-    // Parsing this is actually quite annoying, since it's impossible to assume
-    // that TYER/TDAT/TIME are actually sane, but we try our best.
+    // It's impossible to assume that TYER/TDAT/TIME are actually sane, so we keep
+    // as much of the timestamp as we can and report the parts that were malformed.
 
     let tyer_frame = frames.remove("TYER");
     let tdat_frame = frames.remove("TDAT");
@@ -247,7 +323,7 @@ fn to_tdrc(frames: &mut FrameMap) -> TextFrame {
 
     // Like all text frames, TYER/TDAT/TIME can also contain multiple values. As a result, we keep iterators
     // for all the strings in these frames and zip them together into a timestamp as we go along.
-    let mut tyer = match tyer_frame {
+    let tyer = match tyer_frame {
         Some(ref frame) => frame.downcast::<TextFrame>().unwrap().text.iter(),
         None => [].iter(),
     };
@@ -262,89 +338,50 @@ fn to_tdrc(frames: &mut FrameMap) -> TextFrame {
         None => [].iter(),
     };
 
-    let mut timestamps = Vec::new();
+    let mut tdrc = TextFrame::new(FrameId::new(b"TDRC"));
 
-    // YYYY strings have no defined limit in size, but MMHH/HHMM strings must be 4 characters.
+    // Timestamps are exhausted once TYER is, as there's nothing to attach the date and time to.
+    for year in tyer {
+        let date = tdat.next().map(String::as_str);
+        let time = time.next().map(String::as_str);
 
-    loop {
-        let mut timestamp = String::new();
+        let (year, mut stamp) = match parse_year(FrameId::new(b"TYER"), year, report) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
 
-        match tyer.next() {
-            Some(year) => {
-                if let Some(yyyy) = parse_year(year) {
-                    timestamp.push_str(&yyyy);
-                }
+        if let Some(date) = date {
+            match Timestamp::from_v3(year, Some(date), None) {
+                Ok(with_date) => stamp = with_date,
+                Err(_) => malformed(report, FrameId::new(b"TDAT"), date),
             }
-
-            // Timestamps are now exhausted, exit the loop.
-            _ => break,
         }
 
-        if let Some(date) = tdat.next() {
-            if let Some(mmdd) = parse_quad_digits(date) {
-                timestamp.push_str(&format!["-{}-{}", &mmdd[0..2], &mmdd[2..4]]);
-
-                if let Some(time) = time.next() {
-                    if let Some(hhmm) = parse_quad_digits(time) {
-                        timestamp.push_str(&format!["T{}:{}", &hhmm[0..2], &hhmm[2..4]]);
-                    }
-                }
+        if let (Some(date), Some(time)) = (date, time) {
+            match Timestamp::from_v3(year, Some(date), Some(time)) {
+                Ok(with_time) => stamp = with_time,
+                Err(_) if stamp.day().is_some() => malformed(report, FrameId::new(b"TIME"), time),
+                Err(_) => {}
             }
         }
 
-        if !timestamp.is_empty() {
-            timestamps.push(timestamp)
-        }
+        tdrc.text.push(stamp.to_string())
     }
 
-    let mut tdrc = TextFrame::new(FrameId::new(b"TDRC"));
-    tdrc.text = timestamps;
-
     tdrc
 }
 
-fn from_tdrc(tdrc: &TextFrame, frames: &mut FrameMap) {
+fn from_tdrc(tdrc: &TextFrame, frames: &mut FrameMap, report: &mut UpdateReport) {
     let mut tyer = TextFrame::new(FrameId::new(b"TYER"));
     let mut tdat = TextFrame::new(FrameId::new(b"TDAT"));
     let mut time = TextFrame::new(FrameId::new(b"TIME"));
 
-    // Detect a valid timestamp. This is quite strict, but prevents weird timestamps from
-    // causing malformed data.
-    for stamp in &tdrc.text {
-        let mut chars = stamp.chars();
-
-        // We walk until we hit either:
-        // - A stopping character [-/T/:]
-        // - The end of the iterator
-        // - A non-digit character, which causes parsing to fail.
-
-        // Like parse_year, tolerate years that aren't four chars.
-        match parse_timestamp(&mut chars, '-') {
-            Some(yyyy) if !yyyy.is_empty() => tyer.text.push(yyyy),
-            _ => continue,
-        }
-
-        match (
-            parse_timestamp(&mut chars, '-'),
-            parse_timestamp(&mut chars, 'T'),
-        ) {
-            (Some(mm), Some(dd)) if mm.len() == 2 && dd.len() == 2 => {
-                tdat.text.push(format!["{}{}", dd, mm])
-            }
+    for stamp in parse_timestamps(tdrc, report) {
+        let (yyyy, ddmm, hhmm) = stamp.to_v3();
 
-            _ => continue,
-        }
-
-        match (
-            parse_timestamp(&mut chars, ':'),
-            parse_timestamp(&mut chars, ':'),
-        ) {
-            (Some(hh), Some(mm)) if hh.len() == 2 && mm.len() == 2 => {
-                time.text.push(format!["{}{}", hh, mm])
-            }
-
-            _ => continue,
-        }
+        tyer.text.push(yyyy);
+        tdat.text.extend(ddmm);
+        time.text.extend(hhmm);
     }
 
     if !tyer.is_empty() {
@@ -360,57 +397,245 @@ fn from_tdrc(tdrc: &TextFrame, frames: &mut FrameMap) {
     }
 }
 
-fn parse_timestamp(chars: &mut Chars, sep: char) -> Option<String> {
-    let mut string = String::new();
+fn parse_timestamps(frame: &TextFrame, report: &mut UpdateReport) -> Vec<Timestamp> {
+    let mut stamps = Vec::new();
 
-    loop {
-        match chars.next() {
-            Some(ch) if ch.is_ascii_digit() => string.push(ch),
-            Some(ch) if ch == sep => break,
-            Some(_) => return None,
-            None => break,
+    // Malformed timestamps are reported, but we still keep whatever fields were valid.
+    for text in &frame.text {
+        match Timestamp::parse_prefix(text) {
+            Some((stamp, true)) => stamps.push(stamp),
+            Some((stamp, false)) => {
+                malformed(report, frame.id(), text);
+                stamps.push(stamp)
+            }
+            None => malformed(report, frame.id(), text),
         }
     }
 
-    Some(string)
+    stamps
+}
+
+fn parse_year<'a>(
+    id: FrameId,
+    text: &'a str,
+    report: &mut UpdateReport,
+) -> Option<(&'a str, Timestamp)> {
+    if let Ok(stamp) = Timestamp::from_v3(text, None, None) {
+        return Some((text, stamp));
+    }
+
+    // Taggers will write all sorts of things into year frames, such as "c. 1999" or "1999-03".
+    // Recover the first run of digits from these, but still report them, as anything else
+    // in the frame is lost.
+    malformed(report, id, text);
+
+    let start = text.find(|ch: char| ch.is_ascii_digit())?;
+    let digits = &text[start..];
+    let year = match digits.find(|ch: char| !ch.is_ascii_digit()) {
+        Some(end) => &digits[..end],
+        None => digits,
+    };
+
+    let stamp = Timestamp::from_v3(year, None, None).ok()?;
+    info!("recovered year {} from {}", year, text);
+
+    Some((year, stamp))
 }
 
-fn parse_year(timestamp: &str) -> Option<String> {
-    let mut chars = timestamp.chars();
-    let mut result = String::new();
+fn malformed(report: &mut UpdateReport, id: FrameId, text: &str) {
+    warn!("malformed timestamp in {}: {}", id, text);
+    report.malformed_timestamps.push((id, String::from(text)))
+}
 
-    loop {
-        match chars.next() {
-            Some(ch) if ch.is_ascii_digit() => result.push(ch),
-            Some(_) if result.is_empty() => continue,
-            None if result.is_empty() => return None,
+/// Rewrites the genres in `TCON` into the form used by `to`. This is lossy, so it should only
+/// be done when the tag moves to or from ID3v2.4.
+pub fn convert_genres(frames: &mut FrameMap, to: SaveVersion) {
+    let render: fn(&[Genre]) -> Vec<String> = match to {
+        // Write ID3v1 genres as parenthesized references.
+        SaveVersion::V23 => genres_to_v3,
+        // ID3v2.4 drops the parenthesized genre references in favor of separate strings.
+        SaveVersion::V24 => |genres: &[Genre]| genres.iter().map(Genre::to_v4).collect(),
+    };
 
-            // Tolerate years that aren't 4 chars, but pad them so that they are.
-            _ => return Some(format!["{:0>4}", result].parse().unwrap()),
+    if let Some(tcon) = frames
+        .get_mut("TCON")
+        .and_then(|frame| frame.downcast_mut::<TextFrame>())
+    {
+        let text = render(&tcon.genres());
+
+        if text != tcon.text {
+            info!("normalized TCON genres: {}", text.join(", "));
+            tcon.text = text;
         }
     }
+
+    for frame in frames.get_all_mut(b"CHAP") {
+        let chap = frame.downcast_mut::<ChapterFrame>().unwrap();
+        convert_genres(&mut chap.frames, to);
+    }
+
+    for frame in frames.get_all_mut(b"CTOC") {
+        let ctoc = frame.downcast_mut::<TableOfContentsFrame>().unwrap();
+        convert_genres(&mut ctoc.frames, to);
+    }
 }
 
-fn parse_quad_digits(timestamp: &str) -> Option<String> {
-    let mut chars = timestamp.chars();
-    let mut result = String::new();
+fn genres_to_v3(genres: &[Genre]) -> Vec<String> {
+    // ID3v2.3 references all have to be at the start of the first string, followed by an
+    // optional refinement. Any other names are kept as separate strings, like other text frames.
+    let mut first = String::new();
+    let mut names = Vec::new();
 
-    loop {
-        match chars.next() {
-            Some(ch) if ch.is_ascii_digit() && result.len() < 4 => result.push(ch),
-            Some(_) if result.len() < 4 => result.clear(),
-            None if result.len() < 4 => return None,
-            _ => return Some(result),
+    for genre in genres {
+        match genre {
+            Genre::Name(_) => names.push(genre.to_v3()),
+            genre => first.push_str(&genre.to_v3()),
         }
     }
+
+    let mut names = names.into_iter();
+
+    if let Some(name) = names.next() {
+        first.push_str(&name);
+    }
+
+    if first.is_empty() {
+        return Vec::new();
+    }
+
+    let mut text = vec![first];
+    text.extend(names);
+    text
+}
+
+fn pick_adjustment(frames: &FrameMap, id: &[u8; 4]) -> Option<String> {
+    // Prefer the frame with no description, and then the frame describing the whole track.
+    // Otherwise, just take the first frame.
+    let keys: Vec<String> = frames
+        .get_all(id)
+        .into_iter()
+        .filter(|frame| !frame.is_empty())
+        .map(|frame| frame.key())
+        .collect();
+
+    let is_track = |key: &&String| key[5..].eq_ignore_ascii_case("track");
+
+    keys.iter()
+        .find(|key| key.len() == 5)
+        .or_else(|| keys.iter().find(is_track))
+        .or_else(|| keys.first())
+        .cloned()
+}
+
+fn to_rva2(rvad: &RelativeVolumeFrame) -> RelativeVolumeFrame2 {
+    let mut rva2 = RelativeVolumeFrame2::default();
+
+    // Only the front channels are mandatory in RVAD. The others are zeroed when absent, so
+    // they are only carried over if they actually adjust anything.
+    let channels = [
+        (v24::Channel::FrontRight, rvad.right),
+        (v24::Channel::FrontLeft, rvad.left),
+    ];
+
+    let optional = [
+        (v24::Channel::BackRight, rvad.right_back),
+        (v24::Channel::BackLeft, rvad.left_back),
+        (v24::Channel::FrontCenter, rvad.center),
+        (v24::Channel::Subwoofer, rvad.bass),
+    ]
+    .into_iter()
+    .filter(|(_, adjustment)| adjustment.volume.inner() != 0 || adjustment.peak != 0);
+
+    for (channel, adjustment) in channels.into_iter().chain(optional) {
+        let adjustment = v24::VolumeAdjustment {
+            gain: volume_to_v4(adjustment.volume),
+            peak: v24::Peak(adjustment.peak as f64 / V3_UNITY),
+        };
+
+        rva2.channels.insert(channel, adjustment);
+    }
+
+    rva2
+}
+
+fn to_rvad(rva2: &RelativeVolumeFrame2) -> RelativeVolumeFrame {
+    // Channels that RVAD can't represent fall back to the master volume, if present.
+    // BackCenter and Other have no RVAD field, so they are dropped.
+    let master = rva2.channels.get(&v24::Channel::MasterVolume);
+
+    let channel = |channel: v24::Channel| match rva2.channels.get(&channel).or(master) {
+        Some(adjustment) => v23::VolumeAdjustment {
+            volume: volume_to_v3(adjustment.gain),
+            peak: (adjustment.peak.0 * V3_UNITY).round() as u64,
+        },
+        None => v23::VolumeAdjustment::default(),
+    };
+
+    RelativeVolumeFrame {
+        right: channel(v24::Channel::FrontRight),
+        left: channel(v24::Channel::FrontLeft),
+        right_back: channel(v24::Channel::BackRight),
+        left_back: channel(v24::Channel::BackLeft),
+        center: channel(v24::Channel::FrontCenter),
+        bass: channel(v24::Channel::Subwoofer),
+    }
+}
+
+fn to_equ2(equa: &EqualizationFrame) -> EqualizationFrame2 {
+    let mut equ2 = EqualizationFrame2::new();
+
+    // EQU2 frequencies are in 1/2 hz intervals, so this is the one part that is lossless.
+    for (frequency, volume) in &equa.adjustments {
+        let frequency = v24::Frequency(u16::min(frequency.0, 32767) * 2);
+        equ2.adjustments.insert(frequency, volume_to_v4(*volume));
+    }
+
+    equ2
+}
+
+fn to_equa(equ2: &EqualizationFrame2) -> EqualizationFrame {
+    let mut equa = EqualizationFrame::default();
+
+    // EQUA can't represent half-hz frequencies or interpolation, so frequencies are
+    // rounded down and the interpolation method is ignored.
+    for (frequency, volume) in &equ2.adjustments {
+        let frequency = v23::Frequency(frequency.0 / 2);
+        equa.adjustments
+            .entry(frequency)
+            .or_insert_with(|| volume_to_v3(*volume));
+    }
+
+    equa
+}
+
+fn volume_to_v4(volume: v23::Volume) -> v24::Volume {
+    let ratio = match volume {
+        v23::Volume::Increment(val) => 1.0 + val as f64 / V3_UNITY,
+        v23::Volume::Decrement(val) => 1.0 - val as f64 / V3_UNITY,
+    };
+
+    // Decrements past the unity value would be silence, which ID3v2.4 can't represent.
+    // They're clamped to the lowest volume it allows, which is -64 dB.
+    v24::Volume(f64::max(20.0 * ratio.log10(), -64.0))
+}
+
+fn volume_to_v3(volume: v24::Volume) -> v23::Volume {
+    let change = (10f64.powf(volume.0 / 20.0) - 1.0) * V3_UNITY;
+    let val = change.abs().round() as u64;
+
+    if change > 0.0 {
+        v23::Volume::Increment(val)
+    } else {
+        v23::Volume::Decrement(val)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::id3v2::frames::{
-        EqualizationFrame, EqualizationFrame2, RelativeVolumeFrame, RelativeVolumeFrame2,
-    };
+    use crate::core::string::Encoding;
+    use crate::id3v2::tag::{TagHeader, Version};
+    use std::collections::BTreeMap;
 
     #[test]
     fn upgrade_v3_to_v4() {
@@ -446,10 +671,15 @@ mod tests {
             ..Default::default()
         });
 
-        to_v4(&mut frames);
+        let mut report = UpdateReport::default();
+        to_v4(&mut frames, &mut report);
 
         assert_v4_frames(&frames);
 
+        // TIME is malformed and is reported once for each frame map it's in.
+        let time = (FrameId::new(b"TIME"), String::from("12"));
+        assert_eq!(report.malformed_timestamps, vec![time; 4]);
+
         // Test that we're recursing into metaframes
         let ctoc = frames["CHAP:chp1"].downcast::<ChapterFrame>().unwrap();
         let chap = frames["CTOC:toc1"]
@@ -462,9 +692,9 @@ mod tests {
 
     fn assert_v4_frames(frames: &FrameMap) {
         assert!(!frames.contains_key("RVAD"));
-        assert!(!frames.contains_any(b"RVA2"));
+        assert!(frames.contains_key("RVA2:"));
         assert!(!frames.contains_any(b"EQUA"));
-        assert!(!frames.contains_key("EQU2"));
+        assert!(frames.contains_key("EQU2:"));
 
         assert!(!frames.contains_key("TRDA"));
         assert!(!frames.contains_key("TSIZ"));
@@ -481,6 +711,138 @@ mod tests {
         assert_eq!(frames["TDRC"].to_string(), "2020-10-10");
     }
 
+    #[test]
+    fn upgrade_malformed_dates() {
+        let mut frames = FrameMap::new();
+
+        frames.add(crate::text_frame!(
+            b"TYER",
+            ["2020", "c. 1999", "2021", "Unknown", "1999-03"]
+        ));
+        frames.add(crate::text_frame!(b"TDAT", ["3102", "0101", "1503"]));
+        frames.add(crate::text_frame!(b"TIME", ["1200", "1200", "2561"]));
+        frames.add(crate::text_frame!(b"TORY", ["1999", "Unknown", "1998?"]));
+
+        let mut report = UpdateReport::default();
+        to_v4(&mut frames, &mut report);
+
+        // Years should be recovered from the first run of digits, but still be reported.
+        assert_eq!(
+            frames["TDRC"].to_string(),
+            "2020, 1999-01-01T12:00, 2021-03-15, 1999"
+        );
+        assert_eq!(frames["TDOR"].to_string(), "1999, 1998");
+
+        assert_eq!(
+            report.malformed_timestamps,
+            [
+                (FrameId::new(b"TDAT"), String::from("3102")),
+                (FrameId::new(b"TYER"), String::from("c. 1999")),
+                (FrameId::new(b"TIME"), String::from("2561")),
+                (FrameId::new(b"TYER"), String::from("Unknown")),
+                (FrameId::new(b"TYER"), String::from("1999-03")),
+                (FrameId::new(b"TORY"), String::from("Unknown")),
+                (FrameId::new(b"TORY"), String::from("1998?")),
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_genres() {
+        let mut frames = FrameMap::new();
+        frames.add(crate::text_frame!(
+            b"TCON",
+            Encoding::Latin1,
+            ["(17)Rock", "(RX)Hard Rock", "Shoegaze"]
+        ));
+
+        super::convert_genres(&mut frames, SaveVersion::V24);
+
+        let tcon = frames["TCON"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tcon.text, &["17", "RX", "Hard Rock", "Shoegaze"]);
+
+        super::convert_genres(&mut frames, SaveVersion::V23);
+
+        // References and the first name share a string, while other names are kept separate.
+        let tcon = frames["TCON"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tcon.text, &["(17)(RX)Hard Rock", "Shoegaze"]);
+        assert_eq!(
+            tcon.render(&TagHeader::with_version(Version::V23)),
+            b"\x00(17)(RX)Hard Rock\x00Shoegaze"
+        );
+
+        let mut frames = FrameMap::new();
+        frames.add(crate::text_frame!(b"TCON", ["(Live)", "RX"]));
+        super::convert_genres(&mut frames, SaveVersion::V23);
+
+        let tcon = frames["TCON"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tcon.text, &["(RX)((Live)"]);
+        assert_eq!(
+            tcon.genres(),
+            [Genre::Remix, Genre::Name(String::from("(Live)"))]
+        );
+    }
+
+    #[test]
+    fn round_trip_genres() {
+        let genres = [
+            vec!["Post-Rock", "Shoegaze"],
+            vec!["Pop/Funk"],
+            vec!["17", "RX", "Hard Rock", "(Live)", "Shoegaze"],
+        ];
+
+        for text in genres {
+            let mut tcon = TextFrame::new(FrameId::new(b"TCON"));
+            tcon.text = text.iter().map(|text| text.to_string()).collect();
+
+            let mut frames = FrameMap::new();
+            frames.add(tcon);
+
+            super::convert_genres(&mut frames, SaveVersion::V23);
+            super::convert_genres(&mut frames, SaveVersion::V24);
+
+            let tcon = frames["TCON"].downcast::<TextFrame>().unwrap();
+            assert_eq!(tcon.text, text);
+        }
+    }
+
+    #[test]
+    fn downgrade_duplicate_credits() {
+        let mut frames = FrameMap::new();
+
+        frames.add(crate::credits_frame! {
+            b"TIPL",
+            "Producer" => "Sarah Oliver",
+            "Producer" => "John Smith"
+        });
+
+        frames.add(crate::credits_frame! {
+            b"TMCL",
+            "Bassist" => "John Smith",
+            "Producer" => "Matt Carver"
+        });
+
+        to_v3(
+            &mut frames,
+            CompatibilityPolicy::Strict,
+            &mut UpdateReport::default(),
+        );
+
+        let ipls = frames["TIPL"].downcast::<CreditsFrame>().unwrap();
+        assert_eq!(ipls.id(), b"IPLS");
+        assert_eq!(ipls.people.len(), 4);
+        assert_eq!(
+            ipls.people.get("producer"),
+            ["Sarah Oliver", "John Smith", "Matt Carver"]
+        );
+
+        to_v4(&mut frames, &mut UpdateReport::default());
+
+        let tipl = frames["TIPL"].downcast::<CreditsFrame>().unwrap();
+        assert_eq!(tipl.id(), b"TIPL");
+        assert_eq!(tipl.people.len(), 4);
+    }
+
     #[test]
     fn upgrade_v4_to_v3() {
         const FULL: &str = "2020-01-01T12:34:00";
@@ -541,10 +903,20 @@ mod tests {
             ..Default::default()
         });
 
-        to_v3(&mut frames);
+        let mut report = UpdateReport::default();
+        to_v3(&mut frames, CompatibilityPolicy::Strict, &mut report);
 
         assert_v3_frames(&frames);
 
+        assert_eq!(report.malformed_timestamps.len(), 8);
+        assert_eq!(
+            report.malformed_timestamps[0..2],
+            [
+                (FrameId::new(b"TDRC"), String::from(NO_MIN)),
+                (FrameId::new(b"TDOR"), String::from("2020-10-10T40:40"))
+            ]
+        );
+
         // Test that we're recursing into metaframes
         let ctoc = frames["CHAP:chp1"].downcast::<ChapterFrame>().unwrap();
         let chap = frames["CTOC:toc1"]
@@ -556,8 +928,10 @@ mod tests {
     }
 
     fn assert_v3_frames(frames: &FrameMap) {
-        assert!(!frames.contains_key("RVA2"));
-        assert!(!frames.contains_key("EQU2"));
+        assert!(!frames.contains_any(b"RVA2"));
+        assert!(!frames.contains_any(b"EQU2"));
+        assert!(!frames.contains_key("RVAD"));
+        assert!(!frames.contains_key("EQUA"));
         assert!(!frames.contains_key("ASPI"));
         assert!(!frames.contains_key("SIGN"));
         assert!(!frames.contains_key("SEEK"));
@@ -590,4 +964,198 @@ mod tests {
         assert_eq!(frames["TDAT"].to_string(), "0101, 0202, 0303, 0404");
         assert_eq!(frames["TIME"].to_string(), "1234, 1616");
     }
+
+    #[test]
+    fn downgrade_compatible() {
+        let mut frames = FrameMap::new();
+
+        frames.add(crate::text_frame!(b"TMOO", ["Calm", "Bright"]));
+        frames.add(crate::text_frame!(b"TDRL", ["2020-10-10"]));
+        frames.add(crate::text_frame!(b"TSST", ["Disc One"]));
+        frames.add(crate::text_frame!(b"TSOA", ["Album, The"]));
+        frames.add(crate::text_frame!(b"TSOP", ["Artist, The"]));
+        frames.add(crate::text_frame!(b"TSOT", ["Title, The"]));
+        frames.add(UserTextFrame {
+            desc: String::from("MOOD"),
+            text: vec![String::from("Warm")],
+            ..Default::default()
+        });
+
+        frames.add(ChapterFrame {
+            element_id: String::from("chp1"),
+            frames: frames.clone(),
+            ..Default::default()
+        });
+
+        to_v3(
+            &mut frames,
+            CompatibilityPolicy::Compatible,
+            &mut UpdateReport::default(),
+        );
+
+        assert_compatible_frames(&frames);
+
+        let chap = frames["CHAP:chp1"].downcast::<ChapterFrame>().unwrap();
+        assert_compatible_frames(&chap.frames);
+    }
+
+    fn assert_compatible_frames(frames: &FrameMap) {
+        assert!(!frames.contains_key("TMOO"));
+        assert!(!frames.contains_key("TDRL"));
+        assert!(!frames.contains_key("TSST"));
+
+        assert_eq!(frames["TXXX:MOOD"].to_string(), "Warm, Calm, Bright");
+        assert_eq!(frames["TXXX:RELEASETIME"].to_string(), "2020-10-10");
+        assert_eq!(frames["TXXX:SETSUBTITLE"].to_string(), "Disc One");
+
+        assert_eq!(frames["TSOA"].to_string(), "Album, The");
+        assert_eq!(frames["TSOP"].to_string(), "Artist, The");
+        assert_eq!(frames["TSOT"].to_string(), "Title, The");
+    }
+
+    #[test]
+    fn upgrade_rvad() {
+        let mut frames = FrameMap::new();
+
+        frames.add(RelativeVolumeFrame {
+            right: v23::VolumeAdjustment {
+                volume: v23::Volume::Increment(32768),
+                peak: 16384,
+            },
+            left: v23::VolumeAdjustment {
+                volume: v23::Volume::Decrement(16384),
+                peak: 32768,
+            },
+            bass: v23::VolumeAdjustment {
+                volume: v23::Volume::Decrement(32768),
+                peak: 0,
+            },
+            ..Default::default()
+        });
+
+        to_v4(&mut frames, &mut UpdateReport::default());
+
+        assert!(!frames.contains_key("RVAD"));
+
+        let rva2 = frames["RVA2:"].downcast::<RelativeVolumeFrame2>().unwrap();
+        let right = rva2.channels[&v24::Channel::FrontRight];
+        let left = rva2.channels[&v24::Channel::FrontLeft];
+
+        assert_eq!(rva2.desc, "");
+        assert!((right.gain.0 - 6.0206).abs() < 0.001);
+        assert_eq!(right.peak.0, 0.5);
+        assert!((left.gain.0 + 6.0206).abs() < 0.001);
+        assert_eq!(left.peak.0, 1.0);
+        assert_eq!(rva2.channels[&v24::Channel::Subwoofer].gain.0, -64.0);
+        assert_eq!(rva2.channels.len(), 3);
+
+        // Channels that RVAD did not adjust should not be added.
+        assert!(!rva2.channels.contains_key(&v24::Channel::FrontCenter));
+        assert!(!rva2.channels.contains_key(&v24::Channel::BackRight));
+        assert!(!rva2.channels.contains_key(&v24::Channel::BackLeft));
+        assert!(!rva2.channels.contains_key(&v24::Channel::MasterVolume));
+    }
+
+    #[test]
+    fn downgrade_rva2() {
+        let mut frames = FrameMap::new();
+        let adjustment = |gain, peak| v24::VolumeAdjustment {
+            gain: v24::Volume(gain),
+            peak: v24::Peak(peak),
+        };
+
+        frames.add(RelativeVolumeFrame2 {
+            desc: String::from("Track"),
+            channels: BTreeMap::from([
+                (v24::Channel::MasterVolume, adjustment(-6.0206, 0.25)),
+                (v24::Channel::FrontRight, adjustment(6.0206, 0.5)),
+                (v24::Channel::BackCenter, adjustment(1.0, 1.0)),
+            ]),
+        });
+
+        frames.add(RelativeVolumeFrame2 {
+            desc: String::from("Album"),
+            channels: BTreeMap::from([(v24::Channel::FrontLeft, adjustment(12.0, 0.0))]),
+        });
+
+        to_v3(
+            &mut frames,
+            CompatibilityPolicy::Strict,
+            &mut UpdateReport::default(),
+        );
+
+        assert!(!frames.contains_any(b"RVA2"));
+
+        let rvad = frames["RVAD"].downcast::<RelativeVolumeFrame>().unwrap();
+
+        assert_eq!(rvad.right.volume, v23::Volume::Increment(32768));
+        assert_eq!(rvad.right.peak, 16384);
+        assert_eq!(rvad.left.volume, v23::Volume::Decrement(16384));
+        assert_eq!(rvad.left.peak, 8192);
+        assert_eq!(rvad.center.volume, v23::Volume::Decrement(16384));
+        assert_eq!(rvad.bass.volume, v23::Volume::Decrement(16384));
+    }
+
+    #[test]
+    fn upgrade_equa() {
+        let mut frames = FrameMap::new();
+
+        frames.add(EqualizationFrame {
+            adjustments: BTreeMap::from([
+                (v23::Frequency(100), v23::Volume::Increment(32768)),
+                (v23::Frequency(16000), v23::Volume::Decrement(0)),
+            ]),
+        });
+
+        to_v4(&mut frames, &mut UpdateReport::default());
+
+        assert!(!frames.contains_key("EQUA"));
+
+        let equ2 = frames["EQU2:"].downcast::<EqualizationFrame2>().unwrap();
+
+        assert_eq!(equ2.method, v24::InterpolationMethod::Linear);
+        assert_eq!(equ2.adjustments.len(), 2);
+        assert!((equ2.adjustments[&v24::Frequency(200)].0 - 6.0206).abs() < 0.001);
+        assert_eq!(equ2.adjustments[&v24::Frequency(32000)].0, 0.0);
+    }
+
+    #[test]
+    fn downgrade_equ2() {
+        let mut frames = FrameMap::new();
+
+        frames.add(EqualizationFrame2 {
+            desc: String::from("Other"),
+            adjustments: BTreeMap::from([(v24::Frequency(100), v24::Volume(12.0))]),
+            ..Default::default()
+        });
+
+        frames.add(EqualizationFrame2 {
+            adjustments: BTreeMap::from([
+                (v24::Frequency(200), v24::Volume(6.0206)),
+                (v24::Frequency(201), v24::Volume(-6.0206)),
+                (v24::Frequency(32001), v24::Volume(-6.0206)),
+            ]),
+            ..Default::default()
+        });
+
+        to_v3(
+            &mut frames,
+            CompatibilityPolicy::Strict,
+            &mut UpdateReport::default(),
+        );
+
+        assert!(!frames.contains_any(b"EQU2"));
+
+        let equa = frames["EQUA"].downcast::<EqualizationFrame>().unwrap();
+
+        assert_eq!(equa.adjustments.len(), 2);
+        assert_eq!(
+            equa.adjustments[&v23::Frequency(100)],
+            v23::Volume::Increment(32768)
+        );
+        assert_eq!(
+            equa.adjustments[&v23::Frequency(16000)],
+            v23::Volume::Decrement(16384)
+        );
+    }
 }
@@ -0,0 +1,390 @@
+//! Frame encryption.
+//!
+//! ID3v2.3 and ID3v2.4 allow frames to be encrypted with a vendor-specific method that is
+//! registered in an [`EncryptionRegistrationFrame`](crate::id3v2::frames::EncryptionRegistrationFrame).
+//! Musikr does not implement any encryption methods itself, but one can be provided with a
+//! [`FrameCipher`](FrameCipher) implementation.
+//!
+//! Since the registration frame of a method could be anywhere in a tag, encrypted frames are
+//! only decrypted after the rest of the tag has been parsed. The decrypted frames are then
+//! parsed by the [`FrameParser`](crate::id3v2::frames::FrameParser) like any other frame.
+//! Frames that could not be decrypted remain as [`UnknownFrame`](crate::id3v2::frames::UnknownFrame)
+//! instances.
+//!
+//! Groups of frames can also be signed with a [`SignatureFrame`](crate::id3v2::frames::SignatureFrame).
+//! Signatures are checked and produced by a [`FrameSigner`](FrameSigner) implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use musikr::id3v2::crypto::FrameCipher;
+//!
+//! /// A terrible cipher that should never be used.
+//! struct XorCipher(u8);
+//!
+//! impl FrameCipher for XorCipher {
+//!     fn decrypt(&self, owner: &str, _: u8, data: &[u8]) -> Option<Vec<u8>> {
+//!         if owner != "https://example.com/xor" {
+//!             return None;
+//!         }
+//!
+//!         Some(data.iter().map(|b| b ^ self.0).collect())
+//!     }
+//!
+//!     fn encrypt(&self, owner: &str, method: u8, data: &[u8]) -> Option<Vec<u8>> {
+//!         // XOR is symmetric.
+//!         self.decrypt(owner, method, data)
+//!     }
+//! }
+//! ```
+
+use crate::core::io::BufStream;
+use crate::id3v2::collections::FrameMap;
+use crate::id3v2::frames::{
+    self, EncryptionRegistrationFrame, Frame, FrameData, FrameFlags, FrameId, FrameParser,
+    FrameResult, GroupIdFrame, SignatureFrame, UnknownFrame,
+};
+use crate::id3v2::syncdata;
+use crate::id3v2::tag::{CompressionPolicy, TagHeader, Version};
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+
+/// A handler for encrypted frames.
+///
+/// The `owner` and `method` given to each function are taken from the
+/// [`EncryptionRegistrationFrame`](crate::id3v2::frames::EncryptionRegistrationFrame)
+/// that the frame refers to. Returning `None` from either function will mean that the
+/// frame is left as-is.
+pub trait FrameCipher {
+    /// Decrypts the body of a frame.
+    fn decrypt(&self, owner: &str, method: u8, data: &[u8]) -> Option<Vec<u8>>;
+
+    /// Encrypts the body of a frame.
+    fn encrypt(&self, owner: &str, method: u8, data: &[u8]) -> Option<Vec<u8>>;
+}
+
+impl Debug for dyn FrameCipher + '_ {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "FrameCipher"]
+    }
+}
+
+/// A handler for signed groups of frames.
+///
+/// The `owner` and `group` given to each function are taken from the
+/// [`GroupIdFrame`](crate::id3v2::frames::GroupIdFrame) that the signature refers to. The
+/// signed data is every frame in the group exactly as it is written in the tag, including
+/// the frame headers, in the order that the frames appear. Signature frames themselves are
+/// never included.
+pub trait FrameSigner {
+    /// Produces a signature for the frames of a group. Returning `None` will mean that
+    /// the existing signature is left as-is.
+    fn sign(&self, owner: &str, group: u8, data: &[u8]) -> Option<Vec<u8>>;
+
+    /// Returns whether `signature` is valid for the frames of a group.
+    fn verify(&self, owner: &str, group: u8, data: &[u8], signature: &[u8]) -> bool;
+}
+
+impl Debug for dyn FrameSigner + '_ {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "FrameSigner"]
+    }
+}
+
+/// Decrypts any encrypted frames in `unknowns` and adds them to `frames`, returning the frames
+/// that could not be decrypted.
+pub(crate) fn decrypt_frames(
+    tag_header: &TagHeader,
+    frames: &mut FrameMap,
+    unknowns: Vec<UnknownFrame>,
+    parser: &impl FrameParser,
+    cipher: &dyn FrameCipher,
+) -> Vec<UnknownFrame> {
+    let owners = registered_owners(frames);
+    let mut remaining = Vec::new();
+
+    for unknown in unknowns {
+        match decrypt_frame(tag_header, &unknown, &owners, parser, cipher) {
+            Some((frame, flags)) => {
+                info!("decrypted frame {}", frame.key());
+                frames.add_with_flags(frame, flags)
+            }
+            None => remaining.push(unknown),
+        }
+    }
+
+    remaining
+}
+
+fn decrypt_frame(
+    tag_header: &TagHeader,
+    unknown: &UnknownFrame,
+    owners: &BTreeMap<u8, String>,
+    parser: &impl FrameParser,
+    cipher: &dyn FrameCipher,
+) -> Option<(Box<dyn Frame>, FrameFlags)> {
+    let frame_id = FrameId::try_from(unknown.id()).ok()?;
+    let flags = unknown.flags();
+    let mut stream = BufStream::new(unknown.data());
+    let mut frame_flags = FrameFlags::from_status(tag_header.version(), flags);
+
+    // The extra header data of encrypted frames is ordered differently between versions.
+    // It's not encrypted however, so we can read it before decrypting the frame.
+    let compressed = match tag_header.version() {
+        Version::V24 if flags & 0x4 != 0 => {
+            if flags & 0x40 != 0 {
+                frame_flags.group = Some(stream.read_u8().ok()?);
+            }
+
+            frame_flags.encryption = Some(stream.read_u8().ok()?);
+
+            if flags & 0x1 != 0 || flags & 0x8 != 0 {
+                stream.skip(4).ok()?;
+            }
+
+            flags & 0x8 != 0
+        }
+
+        Version::V23 if flags & 0x40 != 0 => {
+            if flags & 0x80 != 0 {
+                stream.skip(4).ok()?;
+            }
+
+            frame_flags.encryption = Some(stream.read_u8().ok()?);
+
+            if flags & 0x20 != 0 {
+                frame_flags.group = Some(stream.read_u8().ok()?);
+            }
+
+            flags & 0x80 != 0
+        }
+
+        _ => return None,
+    };
+
+    let method = frame_flags.encryption?;
+
+    let owner = match owners.get(&method) {
+        Some(owner) => owner,
+        None => {
+            warn!("no registration for encryption method {:#x}", method);
+            return None;
+        }
+    };
+
+    let mut data = match cipher.decrypt(owner, method, stream.take_rest()) {
+        Some(data) => data,
+        None => {
+            warn!("could not decrypt frame {}", frame_id);
+            return None;
+        }
+    };
+
+    // Frames are compressed before they are encrypted.
+    if compressed {
+        data = frames::inflate_frame(&mut BufStream::new(&data)).ok()?;
+    }
+
+    let data = FrameData::Normal(frame_id, BufStream::new(&data));
+
+    match parser.parse(tag_header, data) {
+        Ok(FrameResult::Frame(frame)) => Some((frame, frame_flags)),
+        _ => None,
+    }
+}
+
+/// Encrypts frames with the methods registered in a [`FrameMap`](FrameMap).
+pub(crate) struct Encryptor<'a> {
+    cipher: &'a dyn FrameCipher,
+    owners: BTreeMap<u8, String>,
+}
+
+impl<'a> Encryptor<'a> {
+    pub(crate) fn new(cipher: &'a dyn FrameCipher, frames: &FrameMap) -> Self {
+        Self {
+            cipher,
+            owners: registered_owners(frames),
+        }
+    }
+
+    pub(crate) fn encrypt(&self, method: u8, data: &[u8]) -> Option<Vec<u8>> {
+        let owner = self.owners.get(&method)?;
+        self.cipher.encrypt(owner, method, data)
+    }
+}
+
+fn registered_owners(frames: &FrameMap) -> BTreeMap<u8, String> {
+    frames
+        .get_all(b"ENCR")
+        .into_iter()
+        .filter_map(|frame| frame.downcast::<EncryptionRegistrationFrame>())
+        .map(|encr| (encr.method, encr.owner.clone()))
+        .collect()
+}
+
+/// Verifies every [`SignatureFrame`](SignatureFrame) in `frames` against the frame data they
+/// were read from. `None` is returned if there were no signatures.
+pub(crate) fn verify_signatures(
+    tag_header: &TagHeader,
+    frames: &FrameMap,
+    data: &[u8],
+    signer: &dyn FrameSigner,
+) -> Option<bool> {
+    let signatures = frames.get_all(b"SIGN");
+
+    if signatures.is_empty() {
+        return None;
+    }
+
+    let owners = registered_groups(frames);
+    let groups = group_data(tag_header.version(), data);
+    let mut matches = true;
+
+    for sign in signatures
+        .into_iter()
+        .filter_map(|frame| frame.downcast::<SignatureFrame>())
+    {
+        let owner = match owners.get(&sign.group) {
+            Some(owner) => owner,
+            None => {
+                warn!("no registration for group {:#x}", sign.group);
+                matches = false;
+                continue;
+            }
+        };
+
+        let group = groups
+            .get(&sign.group)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        if !signer.verify(owner, sign.group, group, &sign.signature) {
+            warn!("signature mismatch for group {:#x}", sign.group);
+            matches = false;
+        }
+    }
+
+    Some(matches)
+}
+
+/// Re-signs `signatures` against the rendered frames in `data`, returning the rendered
+/// signature frames and whether every signature could be produced. Signatures that could
+/// not be produced are left as-is.
+pub(crate) fn sign_frames(
+    tag_header: &TagHeader,
+    frames: &FrameMap,
+    data: &[u8],
+    signatures: &mut [(Box<dyn Frame>, FrameFlags)],
+    signer: &dyn FrameSigner,
+) -> (Vec<u8>, bool) {
+    let owners = registered_groups(frames);
+    let groups = group_data(tag_header.version(), data);
+    let mut result = Vec::new();
+    let mut signed = true;
+
+    for (frame, flags) in signatures.iter_mut() {
+        if let Some(sign) = frame.downcast_mut::<SignatureFrame>() {
+            let group = groups
+                .get(&sign.group)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            match owners
+                .get(&sign.group)
+                .and_then(|owner| signer.sign(owner, sign.group, group))
+            {
+                Some(signature) => sign.signature = signature,
+                None => {
+                    warn!("could not sign group {:#x}", sign.group);
+                    signed = false;
+                }
+            }
+        }
+
+        if frame.is_empty() {
+            continue;
+        }
+
+        // Signature frames are never compressed or encrypted, as they are meant to be readable
+        // by anyone that wants to verify the tag.
+        let flags = FrameFlags {
+            encryption: None,
+            ..*flags
+        };
+
+        let compression = CompressionPolicy::None;
+
+        match frames::render(tag_header, frame.as_ref(), &flags, &compression, None) {
+            Ok(data) => result.extend(data),
+            Err(_) => warn!("could not render frame {}", frame.key()),
+        }
+    }
+
+    (result, signed)
+}
+
+/// Splits the rendered frames in `data` by their group symbol. Signature frames are skipped.
+fn group_data(version: Version, data: &[u8]) -> BTreeMap<u8, Vec<u8>> {
+    let mut groups: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+    let mut pos = 0;
+
+    // ID3v2.2 frames cannot be grouped, and the frame headers of ID3v2.3 and ID3v2.4 only
+    // differ in how the size is encoded.
+    if version == Version::V22 {
+        return groups;
+    }
+
+    while pos + 10 <= data.len() && data[pos] != 0 {
+        let header = &data[pos..pos + 10];
+        let size_bytes = [header[4], header[5], header[6], header[7]];
+        let flags = u16::from_be_bytes([header[8], header[9]]);
+
+        let size = match version {
+            Version::V24 => syncdata::to_u28(size_bytes),
+            _ => u32::from_be_bytes(size_bytes),
+        } as usize;
+
+        let end = usize::min(pos + 10 + size, data.len());
+        let body = &data[pos + 10..end];
+
+        // The group symbol is the first extra byte in ID3v2.4, but comes after the
+        // data length indicator and encryption method in ID3v2.3.
+        let group = match version {
+            Version::V24 if flags & 0x40 != 0 => body.first(),
+            Version::V23 if flags & 0x20 != 0 => {
+                let mut offset = 0;
+
+                if flags & 0x80 != 0 {
+                    offset += 4;
+                }
+
+                if flags & 0x40 != 0 {
+                    offset += 1;
+                }
+
+                body.get(offset)
+            }
+            _ => None,
+        };
+
+        if let Some(&group) = group {
+            if &header[0..4] != b"SIGN" {
+                groups.entry(group).or_default().extend(&data[pos..end]);
+            }
+        }
+
+        pos = end;
+    }
+
+    groups
+}
+
+fn registered_groups(frames: &FrameMap) -> BTreeMap<u8, String> {
+    frames
+        .get_all(b"GRID")
+        .into_iter()
+        .filter_map(|frame| frame.downcast::<GroupIdFrame>())
+        .map(|grid| (grid.group, grid.owner.clone()))
+        .collect()
+}
@@ -11,7 +11,7 @@
 //!
 //! - Trait objects make it easy to implement new frame specifications, even outside of musikr.
 //! - There are numerous frames, each with their own distinct structure. This would make a large
-//! enum extremely cumbersome to use.
+//!   enum extremely cumbersome to use.
 //!
 //! Musikr attempts to alleviate the shortcomings of trait objects by implementing
 //! methods to downcast frames to a concrete type. For example, a `dyn Frame` instance
@@ -155,7 +155,11 @@ pub mod events;
 pub mod file;
 pub mod lyrics;
 pub mod owner;
+pub mod registration;
+pub mod seek;
+pub mod signature;
 pub mod stats;
+pub mod stream;
 pub mod text;
 pub mod url;
 
@@ -167,13 +171,22 @@ pub use events::{EventTimingCodesFrame, SyncedTempoCodesFrame};
 pub use file::{AttachedPictureFrame, GeneralObjectFrame};
 pub use lyrics::{SyncedLyricsFrame, UnsyncLyricsFrame};
 pub use owner::{CommercialFrame, OwnershipFrame, TermsOfUseFrame};
+pub use registration::{EncryptionRegistrationFrame, GroupIdFrame};
+pub use seek::{AudioSeekIndexFrame, MpegLookupFrame, SeekFrame};
+pub use signature::SignatureFrame;
 pub use stats::{PlayCounterFrame, PopularimeterFrame};
+pub use stream::{
+    AudioEncryptionFrame, BufferSizeFrame, LinkedInfoFrame, PositionSyncFrame, ReverbFrame,
+};
 pub use text::{CommentsFrame, CreditsFrame, TextFrame, UserTextFrame};
 pub use url::{UrlFrame, UserUrlFrame};
 
 use crate::core::io::BufStream;
-use crate::id3v2::tag::{TagHeader, Version};
-use crate::id3v2::{compat, syncdata, ParseError, ParseResult, SaveError, SaveResult};
+use crate::id3v2::crypto::Encryptor;
+use crate::id3v2::tag::{CompressionPolicy, TagHeader, Version};
+use crate::id3v2::{
+    compat, syncdata, MalformedReason, ParseError, ParseResult, QuirkKind, SaveError, SaveResult,
+};
 
 use dyn_clone::DynClone;
 use log::{error, info, warn};
@@ -196,13 +209,13 @@ pub trait Frame: Display + Debug + AsAny + DynClone {
     ///
     /// # Custom Frame Considerations
     /// - Certain Frame ID namespaces are reserved. For example, all frames beginning with `T` are
-    /// reserved for text frames, while all frames beginning with `W` are reserved for URL frames.
-    /// Keep this in mind depending on the frame you are intending to create.
+    ///   reserved for text frames, while all frames beginning with `W` are reserved for URL frames.
+    ///   Keep this in mind depending on the frame you are intending to create.
     /// - Generally, all Frame IDs beginning with `X`, `Y`, and `Z` are free for anyone to use, while
-    /// the remaining Frame IDs are reserved by the specification. This is reccomended, but not required
-    /// by musikr.
+    ///   the remaining Frame IDs are reserved by the specification. This is reccomended, but not required
+    ///   by musikr.
     /// - Do not use a Frame ID already used by the standard. This may result in errors in musikr or other
-    /// programs.
+    ///   programs.
     fn id(&self) -> FrameId;
 
     /// Returns the unique key of this frame.
@@ -333,7 +346,9 @@ pub fn downcast_box<T: Frame>(frame: Box<dyn Frame>) -> Result<Box<T>, Box<dyn F
 ///
 /// Musikr cannot parse certain frames, such as encrypted frames or ID3v2.2 frames
 /// that have no ID3v2.3 analogue. If this is the case, then this struct is returned.
-/// `UnknownFrame` instances are immutable and are dropped when a tag is upgraded.
+/// `UnknownFrame` instances are immutable. When a tag is upgraded or downgraded, they
+/// are only kept if their layout does not depend on the tag version. See
+/// [`SaveOptions::legacy_ids`](crate::id3v2::tag::SaveOptions::legacy_ids) for more information.
 ///
 /// An UnknownFrame is **not** a [`Frame`](Frame). They can violate certain invariants
 /// and cannot be added to a [`FrameMap`](crate::id3v2::collections::FrameMap).
@@ -386,8 +401,9 @@ impl UnknownFrame {
 
     /// The data of the frame.
     ///
-    /// This will include the entire frame body, including data length
-    /// indicators and other auxiliary data.
+    /// If the frame could not be decoded, such as when it is encrypted, this will include the
+    /// entire frame body, including data length indicators and other auxiliary data. Otherwise,
+    /// this is the decoded body, starting with the group symbol if the frame is grouped.
     pub fn data(&self) -> &[u8] {
         &self.data
     }
@@ -395,8 +411,135 @@ impl UnknownFrame {
     pub(crate) fn id_str(&self) -> &str {
         str::from_utf8(&self.frame_id).unwrap()
     }
+
+    /// Converts this frame from a `from` tag into a frame that can be written to a `to` tag,
+    /// remapping the flags and any ID3v2.2 ID with `legacy_ids`. `None` is returned if the
+    /// frame data depends on `from`, or if there is no ID for an ID3v2.2 frame.
+    pub(crate) fn migrate(
+        &self,
+        from: Version,
+        to: Version,
+        legacy_ids: &[([u8; 3], FrameId)],
+    ) -> Option<Self> {
+        // ID3v2.2 frames can also show up in ID3v2.3 tags, so go off of the ID length instead.
+        let frame_id = match <&[u8; 4]>::try_from(self.frame_id.as_slice()) {
+            Ok(frame_id) => FrameId::try_new(frame_id).ok()?,
+            Err(_) => {
+                let (_, frame_id) = legacy_ids
+                    .iter()
+                    .find(|(v2_id, _)| v2_id[..] == self.frame_id[..])?;
+
+                *frame_id
+            }
+        };
+
+        if from == to {
+            return Some(Self {
+                frame_id: frame_id.inner().to_vec(),
+                ..self.clone()
+            });
+        }
+
+        // Compression and encryption change the layout of the frame data in both versions,
+        // alongside the unsync flag and data length indicator in ID3v2.4. Grouping can be kept,
+        // as the group symbol is in the same place in both versions.
+        let (layout, group) = format_bits(from);
+
+        if self.flags & layout != 0 {
+            return None;
+        }
+
+        let mut flags = FrameFlags::from_status(from, self.flags).status(to);
+
+        if self.flags & group != 0 {
+            flags |= format_bits(to).1;
+        }
+
+        Some(Self {
+            frame_id: frame_id.inner().to_vec(),
+            flags,
+            data: self.data.clone(),
+        })
+    }
 }
 
+/// Information from a frame header that is not part of the frame itself.
+///
+/// `FrameFlags` are stored alongside each frame in a [`FrameMap`](crate::id3v2::collections::FrameMap),
+/// and will be written back out when the tag is saved.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FrameFlags {
+    /// The group symbol of this frame, if any. This should correspond to the symbol
+    /// of a [`GroupIdFrame`](GroupIdFrame) in the same tag.
+    pub group: Option<u8>,
+    /// The encryption method symbol of this frame, if any. This should correspond to the
+    /// symbol of an [`EncryptionRegistrationFrame`](EncryptionRegistrationFrame) in the same
+    /// tag. If the frame cannot be encrypted when the tag is saved, then saving will fail.
+    pub encryption: Option<u8>,
+    /// Whether this frame should be discarded by taggers that do not know it and alter the tag.
+    /// This has no effect on known frames, but is written back out all the same.
+    pub discard_on_tag_alter: bool,
+    /// Whether this frame should be discarded if the audio data of the file is altered. See
+    /// [`SaveOptions::file_altered`](crate::id3v2::tag::SaveOptions::file_altered).
+    pub discard_on_file_alter: bool,
+    /// Whether this frame is meant to be read-only. Tags with edited read-only frames will not
+    /// be saved unless [`SaveOptions::edit_read_only`](crate::id3v2::tag::SaveOptions::edit_read_only)
+    /// is set. Only frames that were read-only when the tag was read are protected, so setting
+    /// this on a frame has no effect on saving until the tag is read again.
+    pub read_only: bool,
+}
+
+impl FrameFlags {
+    /// Reads the frame status flags from the raw flags of a frame header.
+    pub(crate) fn from_status(version: Version, flags: u16) -> Self {
+        let (tag_alter, file_alter, read_only) = status_bits(version);
+
+        Self {
+            discard_on_tag_alter: flags & tag_alter != 0,
+            discard_on_file_alter: flags & file_alter != 0,
+            read_only: flags & read_only != 0,
+            ..Default::default()
+        }
+    }
+
+    /// Renders the frame status flags into the raw flags of a frame header.
+    pub(crate) fn status(&self, version: Version) -> u16 {
+        let (tag_alter, file_alter, read_only) = status_bits(version);
+        let mut flags = 0;
+
+        if self.discard_on_tag_alter {
+            flags |= tag_alter;
+        }
+
+        if self.discard_on_file_alter {
+            flags |= file_alter;
+        }
+
+        if self.read_only {
+            flags |= read_only;
+        }
+
+        flags
+    }
+}
+
+/// Returns the tag alter, file alter, and read-only flag bits for `version`.
+fn status_bits(version: Version) -> (u16, u16, u16) {
+    match version {
+        Version::V24 => (0x4000, 0x2000, 0x1000),
+        _ => (0x8000, 0x4000, 0x2000),
+    }
+}
+
+/// Returns the flag bits that change the layout of the frame data for `version`, alongside
+/// the grouping flag bit. ID3v2.2 frames have no flags.
+fn format_bits(version: Version) -> (u16, u16) {
+    match version {
+        Version::V22 => (0, 0),
+        Version::V23 => (0x0080 | 0x0040, 0x0020),
+        Version::V24 => (0x0008 | 0x0004 | 0x0002 | 0x0001, 0x0040),
+    }
+}
 
 /// A representation of an ID3v2.3 or ID3v2.4 Frame ID.
 ///
@@ -457,7 +600,7 @@ impl FrameId {
     pub(crate) fn validate(frame_id: &[u8]) -> bool {
         for ch in frame_id {
             // Valid frame IDs can only contain uppercase ASCII chars and numbers.
-            if !(b'A'..=b'Z').contains(ch) && !(b'0'..=b'9').contains(ch) {
+            if !ch.is_ascii_uppercase() && !ch.is_ascii_digit() {
                 return false;
             }
         }
@@ -494,7 +637,7 @@ impl FromStr for FrameId {
         let mut id = [0; 4];
 
         for (i, ch) in s.chars().enumerate() {
-            if !('A'..='Z').contains(&ch) && !('0'..='9').contains(&ch) {
+            if !ch.is_ascii_uppercase() && !ch.is_ascii_digit() {
                 return Err(FrameIdError(()));
             }
 
@@ -587,6 +730,11 @@ impl FrameParser for DefaultFrameParser {
         tag_header: &TagHeader,
         data: FrameData<'a>,
     ) -> ParseResult<FrameResult<'a>> {
+        let frame_id = match &data {
+            FrameData::Legacy(frame_id, _) => String::from_utf8_lossy(frame_id).into_owned(),
+            FrameData::Normal(frame_id, _) => frame_id.as_str().to_string(),
+        };
+
         let result = match data {
             FrameData::Legacy(frame_id, stream) => {
                 self.match_frame_v2(tag_header, frame_id, stream)
@@ -602,6 +750,7 @@ impl FrameParser for DefaultFrameParser {
         match result {
             Ok(frame) => Ok(frame),
             Err(err) => {
+                let err = err.in_frame(&frame_id);
                 error!("frame could not be parsed: {}", err);
 
                 if self.strict {
@@ -636,6 +785,8 @@ impl DefaultFrameParser {
         let frame = match frame_id.as_ref() { 
             // AttatchedPictureFrame is subtly different in ID3v2.2, so we handle it separately.
             b"PIC" => frame!(AttachedPictureFrame::parse_v2(&mut stream)?),
+            // LinkedInfoFrame links to a 3-byte ID in ID3v2.2, so it also must be handled separately.
+            b"LNK" => frame!(LinkedInfoFrame::parse_v2(&mut stream)?),
 
             _ => {
                 // Convert ID3v2.2 frame IDs to their ID3v2.3 analogues, as this preserves the most frames.
@@ -684,11 +835,11 @@ impl DefaultFrameParser {
             // Equalization 2 [Frames 4.12]
             b"EQU2" => frame!(EqualizationFrame2::parse(&mut stream)?),
             // Signature Frame [Frames 4.28]
-            // b"SIGN" => todo!(),
+            b"SIGN" => frame!(SignatureFrame::parse(&mut stream)?),
             // Seek frame [Frames 4.27]
-            // b"SEEK" => todo!(),
+            b"SEEK" => frame!(SeekFrame::parse(&mut stream)?),
             // Audio seek point index [Frames 4.30]
-            // b"ASPI" => todo!(),
+            b"ASPI" => frame!(AudioSeekIndexFrame::parse(&mut stream)?),
             // Not version-specific, go down to general frames
             _ => self.match_frame(tag_header, frame_id, stream)?,
         };
@@ -718,7 +869,7 @@ impl DefaultFrameParser {
             // Event timing codes [Frames 4.5]
             b"ETCO" => frame!(EventTimingCodesFrame::parse(&mut stream)?),
             // MPEG Lookup Codes [Frames 4.6]
-            // b"MLLT" => todo!(),
+            b"MLLT" => frame!(MpegLookupFrame::parse(&mut stream)?),
             // Synchronized tempo codes [Frames 4.7]
             b"SYTC" => frame!(SyncedTempoCodesFrame::parse(&mut stream)?),
             // Unsynchronized Lyrics [Frames 4.8]
@@ -729,7 +880,7 @@ impl DefaultFrameParser {
             b"COMM" => frame!(CommentsFrame::parse(&mut stream)?),
             // (Frames 4.11 & 4.12 are Version-Specific)
             // Reverb [Frames 4.13]
-            // b"RVRB" => todo!(),
+            b"RVRB" => frame!(ReverbFrame::parse(&mut stream)?),
             // Attached Picture [Frames 4.14]
             b"APIC" => frame!(AttachedPictureFrame::parse(&mut stream)?),
             // General Encapsulated Object [Frames 4.15]
@@ -739,13 +890,13 @@ impl DefaultFrameParser {
             // Popularimeter [Frames 4.17]
             b"POPM" => frame!(PopularimeterFrame::parse(&mut stream)?),
             // Relative buffer size [Frames 4.18]
-            // b"RBUF" => todo!(),
+            b"RBUF" => frame!(BufferSizeFrame::parse(&mut stream)?),
             // Audio Encryption [Frames 4.19]
-            // b"AENC" => todo!(),
+            b"AENC" => frame!(AudioEncryptionFrame::parse(&mut stream)?),
             // Linked Information [Frames 4.20]
-            // b"LINK" => todo!(),
+            b"LINK" => frame!(LinkedInfoFrame::parse(&mut stream)?),
             // Position synchronization frame [Frames 4.21]
-            // b"POSS" => todo!(),
+            b"POSS" => frame!(PositionSyncFrame::parse(&mut stream)?),
             // Terms of use frame [Frames 4.22]
             b"USER" => frame!(TermsOfUseFrame::parse(&mut stream)?),
             // Ownership frame [Frames 4.23]
@@ -753,9 +904,9 @@ impl DefaultFrameParser {
             // Commercial frame [Frames 4.24]
             b"COMR" => frame!(CommercialFrame::parse(&mut stream)?),
             // Encryption Registration [Frames 4.25]
-            // b"ENCR" => todo!(),
+            b"ENCR" => frame!(EncryptionRegistrationFrame::parse(&mut stream)?),
             // Group Identification [Frames 4.26]
-            // b"GRID" => todo!(),
+            b"GRID" => frame!(GroupIdFrame::parse(&mut stream)?),
             // Private Frame [Frames 4.27]
             b"PRIV" => frame!(PrivateFrame::parse(&mut stream)?),
             // (Frames 4.28 -> 4.30 are version-specific)
@@ -782,32 +933,118 @@ impl Default for DefaultFrameParser {
 // Internal analogue to FrameResult that returns unknown frames.
 #[derive(Debug)]
 pub(crate) enum ParsedFrame {
-    Frame(Box<dyn Frame>),
+    Frame(Box<dyn Frame>, FrameFlags),
     Unknown(UnknownFrame),
     Dropped,
 }
 
+impl ParsedFrame {
+    fn with_flags(self, version: Version, flags: FrameFlags) -> Self {
+        match self {
+            Self::Frame(frame, _) => Self::Frame(frame, flags),
+            // The data of unknown frames has already been decoded, so only the status flags
+            // still apply to it. The group symbol was read out of the data however, so it has
+            // to be put back in front of it.
+            Self::Unknown(mut unknown) => {
+                unknown.flags = flags.status(version);
+
+                if let Some(group) = flags.group {
+                    unknown.flags |= format_bits(version).1;
+                    unknown.data.insert(0, group);
+                }
+
+                Self::Unknown(unknown)
+            }
+            other => other,
+        }
+    }
+}
+
 impl From<FrameResult<'_>> for ParsedFrame {
     fn from(other: FrameResult) -> ParsedFrame {
         match other {
-            FrameResult::Frame(frame) => Self::Frame(frame),
+            FrameResult::Frame(frame) => Self::Frame(frame, FrameFlags::default()),
             FrameResult::Unknown(data) => Self::Unknown(UnknownFrame::new(data, 0)),
             FrameResult::Dropped => ParsedFrame::Dropped,
         }
     }
 }
 
+// Something found while parsing a frame header that is reported back to the tag, as only
+// the tag knows where the frame is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FrameNote {
+    Quirk(QuirkKind),
+    Malformed(MalformedReason),
+}
+
 pub(crate) fn parse(
     tag_header: &TagHeader,
     stream: &mut BufStream,
     parser: &impl FrameParser,
+) -> ParseResult<ParsedFrame> {
+    parse_with_notes(tag_header, stream, parser, &mut Vec::new())
+}
+
+pub(crate) fn parse_with_notes(
+    tag_header: &TagHeader,
+    stream: &mut BufStream,
+    parser: &impl FrameParser,
+    notes: &mut Vec<FrameNote>,
 ) -> ParseResult<ParsedFrame> {
     // Frame structure differs quite significantly across versions, so we have to
     // handle them separately.
     match tag_header.version() {
         Version::V22 => parse_frame_v2(tag_header, stream, parser),
-        Version::V23 => parse_frame_v3(tag_header, stream, parser),
-        Version::V24 => parse_frame_v4(tag_header, stream, parser),
+        Version::V23 => parse_frame_v3(tag_header, stream, parser, notes),
+        Version::V24 => parse_frame_v4(tag_header, stream, parser, notes),
+    }
+}
+
+/// Returns the ID of the frame header at the start of `data`, if it is valid.
+pub(crate) fn peek_id(version: Version, data: &[u8]) -> Option<String> {
+    let len = match version {
+        Version::V22 => 3,
+        _ => 4,
+    };
+
+    let id = data.get(..len)?;
+
+    if FrameId::validate(id) {
+        Some(String::from_utf8_lossy(id).into())
+    } else {
+        None
+    }
+}
+
+/// Finds the next plausible frame header in `data` after the first byte, returning its
+/// position. A header is plausible if it has a valid ID and a size that fits within `data`.
+pub(crate) fn resync(version: Version, data: &[u8]) -> Option<usize> {
+    (1..data.len()).find(|&pos| is_header(version, &data[pos..]))
+}
+
+/// Returns whether `data` starts with a plausible frame header. See [`resync`](resync).
+pub(crate) fn is_header(version: Version, data: &[u8]) -> bool {
+    if peek_id(version, data).is_none() {
+        return false;
+    }
+
+    let fits = |header_len: usize, size: u32| {
+        size > 0 && (size as usize).saturating_add(header_len) <= data.len()
+    };
+
+    match version {
+        Version::V22 if data.len() >= 6 => {
+            fits(6, u32::from_be_bytes([0, data[3], data[4], data[5]]))
+        }
+        Version::V23 | Version::V24 if data.len() >= 10 => {
+            let size_bytes = [data[4], data[5], data[6], data[7]];
+
+            // Like parse_frame_v4, tolerate ID3v2.4 frames with non-syncsafe sizes.
+            fits(10, u32::from_be_bytes(size_bytes))
+                || (version == Version::V24 && fits(10, syncdata::to_u28(size_bytes)))
+        }
+        _ => false,
     }
 }
 
@@ -820,7 +1057,7 @@ fn parse_frame_v2(
     let frame_id = stream.read_array::<3>()?;
 
     if !FrameId::validate(&frame_id) {
-        return Err(ParseError::MalformedData);
+        return Err(ParseError::malformed(MalformedReason::BadFrameId));
     }
 
     // Make u32::from_be_bytes handle the weird 3-byte sizes
@@ -840,6 +1077,7 @@ fn parse_frame_v3(
     tag_header: &TagHeader,
     stream: &mut BufStream,
     parser: &impl FrameParser,
+    notes: &mut Vec<FrameNote>,
 ) -> ParseResult<ParsedFrame> {
     let id_bytes = stream.read_array()?;
     let size = stream.read_be_u32()? as usize;
@@ -848,6 +1086,7 @@ fn parse_frame_v3(
     // Technically, the spec says that empty frames should be a sign of a malformed tag, but they're
     // so common to the point where we should just skip them so other frames can be found.
     if size == 0 {
+        notes.push(FrameNote::Quirk(QuirkKind::EmptyFrame));
         return Ok(ParsedFrame::Dropped);
     }
 
@@ -866,6 +1105,7 @@ fn parse_frame_v3(
         Err(_) => {
             if FrameId::validate(&id_bytes[0..3]) && id_bytes[3] == 0 {
                 info!("correcting incorrect ID3v2.2 frame ID");
+                notes.push(FrameNote::Quirk(QuirkKind::LegacyFrameId));
 
                 let mut v2_id = [0; 3];
                 v2_id.copy_from_slice(&id_bytes[0..3]);
@@ -875,61 +1115,72 @@ fn parse_frame_v3(
                 ));
             }
 
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadFrameId));
         }
     };
 
-    // Encryption. This really can't be implemented since:
-    // A. Encryption is vendor-specific
-    // B. Even if we *were* to add a Fn-pointer for the end-user handle these frames, the end-user
-    // still doesn't know what kind of encryption the frame might have since the corresponding ENCR
-    // frame might have not even been parsed yet.
+    // Encryption. This can't be handled here since the corresponding ENCR frame might have not
+    // even been parsed yet, so we leave these as unknown frames and let the crypto module decrypt
+    // them once all other frames have been parsed.
     //
     // The way encryption is designed in ID3v2.3 and ID3v2.4 is absolutely busted, and honestly it
     // would be so much better if a metaframe like ID3v2.2's CRM was used instead. Oh well.
     if flags & 0x40 != 0 {
-        warn!("encryption is not supported");
+        info!("found encrypted frame {}", frame_id);
         return Ok(ParsedFrame::Unknown(UnknownFrame::new(
             FrameData::Normal(frame_id, stream),
             flags,
         )));
     }
 
+    let mut frame_flags = FrameFlags::from_status(Version::V23, flags);
+
     // Frame-specific compression. This flag also adds a data length indicator that we will skip.
     if flags & 0x80 != 0 {
         stream.skip(4)?;
+    }
 
+    // Frame grouping. The group symbol comes after the data length indicator, but before
+    // the compressed data.
+    if flags & 0x20 != 0 && stream.len() >= 4 {
+        frame_flags.group = Some(stream.read_u8()?);
+    }
+
+    if flags & 0x80 != 0 {
         decoded = match inflate_frame(&mut stream) {
             Ok(stream) => stream,
-            Err(_) => {
+            Err(err) => {
+                if let ParseError::MalformedData(malformed) = err {
+                    notes.push(FrameNote::Malformed(malformed.reason));
+                }
+
                 return Ok(ParsedFrame::Unknown(UnknownFrame::new(
                     FrameData::Normal(frame_id, stream),
                     flags,
-                )))
+                )));
             }
         };
 
         stream = BufStream::new(&decoded);
     }
 
-    // Frame grouping. Pretty much nobody uses this, so its ignored.
-    if flags & 0x20 != 0 && stream.len() >= 4 {
-        stream.skip(1)?;
-    }
+    // Only pass on what is left of the data, so that unknown frames don't keep any of the
+    // fields that were read above.
+    let stream = BufStream::new(stream.take_rest());
+    let parsed = ParsedFrame::from(parser.parse(tag_header, FrameData::Normal(frame_id, stream))?);
 
-    return Ok(ParsedFrame::from(
-        parser.parse(tag_header, FrameData::Normal(frame_id, stream))?,
-    ));
+    Ok(parsed.with_flags(Version::V23, frame_flags))
 }
 
 fn parse_frame_v4(
     tag_header: &TagHeader,
     stream: &mut BufStream,
     parser: &impl FrameParser,
+    notes: &mut Vec<FrameNote>,
 ) -> ParseResult<ParsedFrame> {
     let frame_id = match FrameId::try_new(&stream.read_array()?) {
         Ok(id) => id,
-        Err(_) => return Err(ParseError::MalformedData),
+        Err(_) => return Err(ParseError::malformed(MalformedReason::BadFrameId)),
     };
 
     // ID3v2.4 sizes *should* be syncsafe, but iTunes wrote v2.3-style sizes for awhile. Fix that.
@@ -954,6 +1205,7 @@ fn parse_frame_v4(
 
             if FrameId::validate(&next_id) {
                 info!("correcting non-syncsafe ID3v2.4 frame size");
+                notes.push(FrameNote::Quirk(QuirkKind::NonSyncsafeFrameSize));
                 size = v3_size;
             }
         }
@@ -964,6 +1216,7 @@ fn parse_frame_v4(
     // Technically, the spec says that empty frames should be a sign of a malformed tag, but they're
     // so common to the point where we should just skip them so other frames can be found.
     if size == 0 {
+        notes.push(FrameNote::Quirk(QuirkKind::EmptyFrame));
         return Ok(ParsedFrame::Dropped);
     }
 
@@ -981,26 +1234,33 @@ fn parse_frame_v4(
         stream = BufStream::new(&decoded);
     }
 
-    // Frame grouping. Pretty much nobody uses this, so its ignored.
-    if flags & 0x40 != 0 {
-        stream.skip(1)?;
-    }
-
-    // Encryption is unimplemented, see parse_frame_v3 for more information.
+    // Encryption is deferred, see parse_frame_v3 for more information. This is checked
+    // before the group symbol is read so that it remains in the data of the unknown frame.
     if flags & 0x4 != 0 {
-        warn!("encryption is not supported");
+        info!("found encrypted frame {}", frame_id);
         return Ok(ParsedFrame::Unknown(UnknownFrame::new(
             FrameData::Normal(frame_id, stream),
             flags,
         )));
     }
 
+    let mut frame_flags = FrameFlags::from_status(Version::V24, flags);
+
+    // Frame grouping.
+    if flags & 0x40 != 0 {
+        frame_flags.group = Some(stream.read_u8()?);
+    }
+
     // Data length indicator. Some taggers may not flip the data length indicator when
     // compression is enabled, so it's treated as implicitly enabling it.
     // The spec is also vague about whether the length location is affected by the new flag
     // or the existing compression/encryption flags, so we just assume its the latter.
     // Not like it really matters since we always skip this.
     if flags & 0x1 != 0 || flags & 0x8 != 0 {
+        if flags & 0x1 == 0 {
+            notes.push(FrameNote::Quirk(QuirkKind::MissingDataLengthIndicator));
+        }
+
         stream.skip(4)?;
     }
 
@@ -1008,53 +1268,171 @@ fn parse_frame_v4(
     if flags & 0x8 != 0 {
         decoded = match inflate_frame(&mut stream) {
             Ok(stream) => stream,
-            Err(_) => {
+            Err(err) => {
+                if let ParseError::MalformedData(malformed) = err {
+                    notes.push(FrameNote::Malformed(malformed.reason));
+                }
+
                 return Ok(ParsedFrame::Unknown(UnknownFrame::new(
                     FrameData::Normal(frame_id, stream),
                     flags,
-                )))
+                )));
             }
         };
 
         stream = BufStream::new(&decoded);
     }
 
-    return Ok(ParsedFrame::from(
-        parser.parse(tag_header, FrameData::Normal(frame_id, stream))?,
-    ));
+    // Only pass on what is left of the data, so that unknown frames don't keep any of the
+    // fields that were read above.
+    let stream = BufStream::new(stream.take_rest());
+    let parsed = ParsedFrame::from(parser.parse(tag_header, FrameData::Normal(frame_id, stream))?);
+
+    Ok(parsed.with_flags(Version::V24, frame_flags))
 }
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "id3v2_compression")] {
-        fn inflate_frame(src: &mut BufStream) -> ParseResult<Vec<u8>> {
+        pub(crate) fn inflate_frame(src: &mut BufStream) -> ParseResult<Vec<u8>> {
             miniz_oxide::inflate::decompress_to_vec_zlib(src.take_rest()).map_err(|err| {
                 warn!("decompression failed: {:?}", err);
-                ParseError::MalformedData
+                ParseError::malformed(MalformedReason::DecompressionFailed)
             })
         }
     } else {
-        fn inflate_frame(src: &mut BufStream) -> ParseResult<Vec<u8>> {
+        pub(crate) fn inflate_frame(_: &mut BufStream) -> ParseResult<Vec<u8>> {
             warn!("decompression is not enabled");
             Err(ParseError::Unsupported)
         }
     }
 }
 
-pub(crate) fn render(tag_header: &TagHeader, frame: &dyn Frame) -> SaveResult<Vec<u8>> {
+cfg_if::cfg_if! {
+    if #[cfg(feature = "id3v2_compression")] {
+        fn deflate_frame(src: &[u8]) -> Option<Vec<u8>> {
+            Some(miniz_oxide::deflate::compress_to_vec_zlib(src, 6))
+        }
+    } else {
+        fn deflate_frame(_: &[u8]) -> Option<Vec<u8>> {
+            warn!("compression is not enabled");
+            None
+        }
+    }
+}
+
+pub(crate) fn render(
+    tag_header: &TagHeader,
+    frame: &dyn Frame,
+    frame_flags: &FrameFlags,
+    compression: &CompressionPolicy,
+    encryptor: Option<&Encryptor>,
+) -> SaveResult<Vec<u8>> {
     assert_ne!(tag_header.version(), Version::V22);
 
     // We need to render the frame backwards, starting from the frame and then making the
     // header from the size of that data.
 
     // Render the frame here, as we will need its size.
-    let frame_data = frame.render(tag_header);
+    let mut frame_data = frame.render(tag_header);
     let mut data: Vec<u8> = Vec::new();
 
-    // Render the header. Leave the flags zeroed, we don't care about them and likely
-    // never will.
+    let mut flags = frame_flags.status(tag_header.version());
+    let mut prefix_len = 0;
+
+    // Frame-specific compression. Both versions prepend the decompressed size to the
+    // compressed data, with ID3v2.4 requiring a seperate data length indicator flag to
+    // be set as well.
+    if compression.applies(frame.id(), frame_data.len()) {
+        if let Some(compressed) = deflate_frame(&frame_data) {
+            if compressed.len() + 4 < frame_data.len() {
+                let size = frame_data.len() as u32;
+
+                frame_data = match tag_header.version() {
+                    Version::V24 => {
+                        flags |= 0x8 | 0x1;
+                        syncdata::from_u28(size).to_vec()
+                    }
+                    Version::V23 => {
+                        flags |= 0x80;
+                        size.to_be_bytes().to_vec()
+                    }
+                    Version::V22 => unreachable!(),
+                };
+
+                frame_data.extend(compressed);
+                prefix_len = 4;
+            } else {
+                info!("frame {} is not worth compressing", frame.key());
+            }
+        }
+    }
+
+    // Frame-specific encryption. This is done after compression, and leaves the decompressed
+    // size unencrypted. The method symbol is placed before the data length indicator in
+    // ID3v2.4, and after it in ID3v2.3.
+    if let Some(method) = frame_flags.encryption {
+        let size = frame_data.len() - prefix_len;
+
+        let encrypted = match encryptor.and_then(|e| e.encrypt(method, &frame_data[prefix_len..])) {
+            Some(encrypted) => encrypted,
+            None => {
+                error!("could not encrypt frame {}", frame.key());
+                return Err(SaveError::Unencrypted);
+            }
+        };
+
+        frame_data.truncate(prefix_len);
+        frame_data.extend(encrypted);
+
+        match tag_header.version() {
+            Version::V24 => {
+                // A data length indicator is recommended for encrypted frames.
+                if prefix_len == 0 {
+                    frame_data.splice(0..0, syncdata::from_u28(size as u32));
+                    prefix_len = 4;
+                    flags |= 0x1;
+                }
+
+                flags |= 0x4;
+                frame_data.insert(0, method);
+            }
+            Version::V23 => {
+                flags |= 0x40;
+                frame_data.insert(prefix_len, method);
+                prefix_len += 1;
+            }
+            Version::V22 => unreachable!(),
+        }
+    }
+
+    // Frame grouping. ID3v2.4 places the group symbol before all other header data, while
+    // ID3v2.3 places it after.
+    if let Some(group) = frame_flags.group {
+        match tag_header.version() {
+            Version::V24 => {
+                flags |= 0x40;
+                frame_data.insert(0, group);
+            }
+            Version::V23 => {
+                flags |= 0x20;
+                frame_data.insert(prefix_len, group);
+            }
+            Version::V22 => unreachable!(),
+        }
+    }
+
+    // ID3v2.4 unsynchronization is done per-frame, so we must encode the frame and flip
+    // the unsynchronization flag if the tag is unsynchronized.
+
+    if tag_header.version() == Version::V24 && tag_header.flags().unsync {
+        frame_data = syncdata::encode(&frame_data);
+        flags |= 0x2;
+    }
+
+    // Render the header.
     data.extend(match tag_header.version() {
-        Version::V24 => render_v4_header(frame.id(), 0, frame_data.len())?,
-        Version::V23 => render_v3_header(frame.id(), 0, frame_data.len())?,
+        Version::V24 => render_v4_header(frame.id(), flags, frame_data.len())?,
+        Version::V23 => render_v3_header(frame.id(), flags, frame_data.len())?,
         Version::V22 => unreachable!(),
     });
 
@@ -1077,17 +1455,28 @@ pub(crate) fn render_unknown(tag_header: &TagHeader, frame: &UnknownFrame) -> Ve
     let mut data: Vec<u8> = Vec::new();
 
     // UnknownFrame instances are immutable, so we can assume that they will render with no issues.
-    // We also re-render the unknown frame flags as well, exlcuding the ID3v2.4 unsync flag, as we don't
-    // resynchronize frames.
-    data.extend(match tag_header.version() {
+    // We also re-render the unknown frame flags as well. The data of unknown frames has already been
+    // decoded, so the ID3v2.4 unsync flag is only kept if we are going to re-encode the frame.
+    match tag_header.version() {
+        Version::V24 if tag_header.flags().unsync => {
+            let frame_data = syncdata::encode(frame.data());
+            let flags = frame.flags() | 0x2;
+
+            data.extend(render_v4_header(frame_id, flags, frame_data.len()).unwrap());
+            data.extend(frame_data);
+        }
         Version::V24 => {
-            render_v4_header(frame_id, frame.flags() & 0xFFFD, frame.data().len()).unwrap()
+            let flags = frame.flags() & 0xFFFD;
+
+            data.extend(render_v4_header(frame_id, flags, frame.data().len()).unwrap());
+            data.extend(frame.data());
+        }
+        Version::V23 => {
+            data.extend(render_v3_header(frame_id, frame.flags(), frame.data().len()).unwrap());
+            data.extend(frame.data());
         }
-        Version::V23 => render_v3_header(frame_id, frame.flags(), frame.data().len()).unwrap(),
         Version::V22 => unreachable!(),
-    });
-
-    data.extend(frame.data());
+    }
 
     data
 }
@@ -1110,7 +1499,7 @@ fn render_v3_header(frame_id: FrameId, flags: u16, size: usize) -> SaveResult<[u
     data[4..8].copy_from_slice(&size.to_be_bytes());
 
     // Render flags.
-    data[8] = (flags & 0xFF00) as u8;
+    data[8] = (flags >> 8) as u8;
     data[9] = (flags & 0x00FF) as u8;
 
     Ok(data)
@@ -1131,7 +1520,7 @@ fn render_v4_header(frame_id: FrameId, flags: u16, size: usize) -> SaveResult<[u
     data[4..8].copy_from_slice(&syncdata::from_u28(size as u32));
 
     // Render flags.
-    data[8] = (flags & 0xFF00) as u8;
+    data[8] = (flags >> 8) as u8;
     data[9] = (flags & 0x00FF) as u8;
 
     Ok(data)
@@ -1140,7 +1529,6 @@ fn render_v4_header(frame_id: FrameId, flags: u16, size: usize) -> SaveResult<[u
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::id3v2::frames::file::PictureType;
     use crate::id3v2::Tag;
     use std::env;
     use std::ops::Deref;
@@ -1150,7 +1538,10 @@ mod tests {
     const DATA_V4: &[u8] = b"TIT2\x00\x00\x00\x09\x00\x00\x00Unspoken";
 
     #[test]
+    #[cfg(feature = "id3v2_compression")]
     fn parse_compressed_frames() {
+        use crate::id3v2::frames::file::PictureType;
+
         let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/compressed.mp3";
         let tag = Tag::open(&path).unwrap();
         let apic = &tag.frames["APIC:"]
@@ -1162,6 +1553,63 @@ mod tests {
         assert_eq!(apic.picture.len(), 86414);
     }
 
+    #[test]
+    #[cfg(feature = "id3v2_compression")]
+    fn render_compressed_frames() {
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/compressed.mp3";
+        let tag = Tag::open(&path).unwrap();
+        let apic = &tag.frames["APIC:"];
+        let policy = CompressionPolicy::LargerThan(1024);
+        let flags = FrameFlags::default();
+
+        let tag_header = TagHeader::with_version(Version::V23);
+        let data = render(&tag_header, apic, &flags, &policy, None).unwrap();
+        assert_eq!(&data[8..10], b"\x00\x80");
+        assert!(data.len() < 86414);
+
+        let tag_header = TagHeader::with_version(Version::V24);
+        let data = render(&tag_header, apic, &flags, &policy, None).unwrap();
+        assert_eq!(&data[8..10], b"\x00\x09");
+        assert!(data.len() < 86414);
+
+        // Small frames should not be compressed with this policy.
+        let tit2 = &tag.frames["TIT2"];
+        let data = render(&tag_header, tit2, &flags, &policy, None).unwrap();
+        assert_eq!(&data[8..10], b"\x00\x00");
+    }
+
+    #[test]
+    #[cfg(feature = "id3v2_compression")]
+    fn write_compressed_frames() {
+        use crate::id3v2::tag::{SaveOptions, SaveVersion};
+
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/compressed.mp3";
+
+        for version in [SaveVersion::V23, SaveVersion::V24] {
+            let mut tag = Tag::open(&path).unwrap();
+            tag.update(version);
+
+            let out = env::temp_dir().join("musikr_compressed_out.mp3");
+            std::fs::write(&out, []).unwrap();
+
+            let options = SaveOptions {
+                compression: CompressionPolicy::Frames(vec![FrameId::new(b"APIC")]),
+                ..Default::default()
+            };
+
+            tag.save_with(&out, &options).unwrap();
+            assert!(tag.size() < 86414);
+
+            let tag = Tag::open(&out).unwrap();
+            let apic = &tag.frames["APIC:"]
+                .downcast::<AttachedPictureFrame>()
+                .unwrap();
+
+            assert_eq!(apic.mime, "image/bmp");
+            assert_eq!(apic.picture.len(), 86414);
+        }
+    }
+
     #[test]
     fn parse_itunes_frame_sizes() {
         let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/itunes_sizes.mp3";
@@ -1182,10 +1630,17 @@ mod tests {
         )
         .unwrap();
 
-        if let ParsedFrame::Frame(frame) = frame {
+        if let ParsedFrame::Frame(frame, _) = frame {
             assert_eq!(frame.id(), b"TIT2");
             assert_eq!(
-                render(&TagHeader::with_version(Version::V23), frame.deref()).unwrap(),
+                render(
+                    &TagHeader::with_version(Version::V23),
+                    frame.deref(),
+                    &FrameFlags::default(),
+                    &CompressionPolicy::None,
+                    None
+                )
+                .unwrap(),
                 DATA_V3
             );
         } else {
@@ -1202,10 +1657,17 @@ mod tests {
         )
         .unwrap();
 
-        if let ParsedFrame::Frame(frame) = frame {
+        if let ParsedFrame::Frame(frame, _) = frame {
             assert_eq!(frame.id(), b"TIT2");
             assert_eq!(
-                render(&TagHeader::with_version(Version::V24), frame.deref()).unwrap(),
+                render(
+                    &TagHeader::with_version(Version::V24),
+                    frame.deref(),
+                    &FrameFlags::default(),
+                    &CompressionPolicy::None,
+                    None
+                )
+                .unwrap(),
                 DATA_V4
             );
         } else {
@@ -1222,10 +1684,17 @@ mod tests {
         )
         .unwrap();
 
-        if let ParsedFrame::Frame(frame) = frame {
+        if let ParsedFrame::Frame(frame, _) = frame {
             assert_eq!(frame.id(), b"TIT2");
             assert_eq!(
-                render(&TagHeader::with_version(Version::V23), frame.deref()).unwrap(),
+                render(
+                    &TagHeader::with_version(Version::V23),
+                    frame.deref(),
+                    &FrameFlags::default(),
+                    &CompressionPolicy::None,
+                    None
+                )
+                .unwrap(),
                 DATA_V3
             );
         } else {
@@ -1233,6 +1702,211 @@ mod tests {
         }
     }
 
+    #[test]
+    fn round_trip_duplicate_credits() {
+        const PEOPLE: &[u8] = b"\x00\
+                                Producer\0Sarah Oliver\0\
+                                Mixer\0Matt Carver\0\
+                                Producer\0John Smith";
+
+        let data_v2 = [b"IPL\x00\x00\x3C".as_ref(), PEOPLE].concat();
+        let data_v3 = [b"IPLS\x00\x00\x00\x3C\x00\x00".as_ref(), PEOPLE].concat();
+        let data_v4 = [b"TIPL\x00\x00\x00\x3C\x00\x00".as_ref(), PEOPLE].concat();
+
+        // ID3v2.2 frames can only be written as ID3v2.3 frames.
+        for (from, to, data, expected) in [
+            (Version::V22, Version::V23, &data_v2, &data_v3),
+            (Version::V23, Version::V23, &data_v3, &data_v3),
+            (Version::V24, Version::V24, &data_v4, &data_v4),
+        ] {
+            let frame = match parse(
+                &TagHeader::with_version(from),
+                &mut BufStream::new(data),
+                &DefaultFrameParser { strict: true },
+            ) {
+                Ok(ParsedFrame::Frame(frame, _)) => frame,
+                _ => panic!("frame was not parsed"),
+            };
+
+            let credits = frame.downcast::<CreditsFrame>().unwrap();
+            assert_eq!(
+                credits.people.get("producer"),
+                ["Sarah Oliver", "John Smith"]
+            );
+
+            assert_eq!(
+                &render(
+                    &TagHeader::with_version(to),
+                    frame.deref(),
+                    &FrameFlags::default(),
+                    &CompressionPolicy::None,
+                    None
+                )
+                .unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn parse_grouped_frames() {
+        let data_v3 = b"TIT2\x00\x00\x00\x0A\x00\x20\x80\x00Unspoken";
+        let data_v4 = b"TIT2\x00\x00\x00\x0A\x00\x40\x80\x00Unspoken";
+
+        for (version, data) in [(Version::V23, &data_v3[..]), (Version::V24, &data_v4[..])] {
+            let tag_header = TagHeader::with_version(version);
+            let frame = parse(
+                &tag_header,
+                &mut BufStream::new(data),
+                &DefaultFrameParser { strict: true },
+            )
+            .unwrap();
+
+            if let ParsedFrame::Frame(frame, flags) = frame {
+                assert_eq!(frame.to_string(), "Unspoken");
+                assert_eq!(flags.group, Some(0x80));
+                assert_eq!(
+                    render(
+                        &tag_header,
+                        frame.deref(),
+                        &flags,
+                        &CompressionPolicy::None,
+                        None
+                    )
+                    .unwrap(),
+                    data
+                );
+            } else {
+                panic!("frame was not parsed");
+            }
+        }
+    }
+
+    #[test]
+    fn parse_status_flags() {
+        let data_v3 = b"TIT2\x00\x00\x00\x09\xE0\x00\x00Unspoken";
+        let data_v4 = b"TIT2\x00\x00\x00\x09\x70\x00\x00Unspoken";
+
+        for (version, data) in [(Version::V23, &data_v3[..]), (Version::V24, &data_v4[..])] {
+            let tag_header = TagHeader::with_version(version);
+            let frame = parse(
+                &tag_header,
+                &mut BufStream::new(data),
+                &DefaultFrameParser { strict: true },
+            )
+            .unwrap();
+
+            if let ParsedFrame::Frame(frame, flags) = frame {
+                assert!(flags.discard_on_tag_alter);
+                assert!(flags.discard_on_file_alter);
+                assert!(flags.read_only);
+                assert_eq!(
+                    render(
+                        &tag_header,
+                        frame.deref(),
+                        &flags,
+                        &CompressionPolicy::None,
+                        None
+                    )
+                    .unwrap(),
+                    data
+                );
+            } else {
+                panic!("frame was not parsed");
+            }
+        }
+    }
+
+    #[test]
+    fn migrate_unknown_frames() {
+        let unknown = |frame_id: &[u8], flags| UnknownFrame {
+            frame_id: frame_id.to_vec(),
+            flags,
+            data: b"\x80data".to_vec(),
+        };
+
+        // Status and grouping flags should be remapped.
+        let frame = unknown(b"NCON", 0xA020)
+            .migrate(Version::V23, Version::V24, &[])
+            .unwrap();
+
+        assert_eq!(frame.id(), b"NCON");
+        assert_eq!(frame.flags(), 0x5040);
+        assert_eq!(frame.data(), b"\x80data");
+
+        let frame = frame.migrate(Version::V24, Version::V23, &[]).unwrap();
+        assert_eq!(frame.flags(), 0xA020);
+
+        // Frames whose data depends on the version should be dropped.
+        assert!(unknown(b"NCON", 0x0080)
+            .migrate(Version::V23, Version::V24, &[])
+            .is_none());
+        assert!(unknown(b"NCON", 0x0040)
+            .migrate(Version::V23, Version::V24, &[])
+            .is_none());
+        assert!(unknown(b"NCON", 0x0002)
+            .migrate(Version::V24, Version::V23, &[])
+            .is_none());
+        assert!(unknown(b"NCON", 0x0001)
+            .migrate(Version::V24, Version::V23, &[])
+            .is_none());
+
+        // ID3v2.2 frames need an ID to be written with.
+        let legacy_ids = [(*b"XSP", FrameId::new(b"XSOP"))];
+
+        assert!(unknown(b"XSP", 0)
+            .migrate(Version::V22, Version::V23, &[])
+            .is_none());
+
+        let frame = unknown(b"XSP", 0)
+            .migrate(Version::V22, Version::V23, &legacy_ids)
+            .unwrap();
+
+        assert_eq!(frame.id(), b"XSOP");
+        assert_eq!(frame.flags(), 0);
+
+        // This also applies to ID3v2.2 frames in ID3v2.3 tags.
+        let frame = unknown(b"XSP", 0)
+            .migrate(Version::V23, Version::V23, &legacy_ids)
+            .unwrap();
+
+        assert_eq!(frame.id(), b"XSOP");
+    }
+
+    #[test]
+    #[cfg(feature = "id3v2_compression")]
+    fn render_grouped_compressed_frames() {
+        use crate::core::string::Encoding;
+
+        let mut frame = TextFrame::new(FrameId::new(b"TIT2"));
+        frame.encoding = Encoding::Latin1;
+        frame.text = vec![String::from("Unspoken").repeat(16)];
+
+        let flags = FrameFlags {
+            group: Some(0x80),
+            ..Default::default()
+        };
+
+        for version in [Version::V23, Version::V24] {
+            let tag_header = TagHeader::with_version(version);
+            let data = render(&tag_header, &frame, &flags, &CompressionPolicy::All, None).unwrap();
+
+            let parsed = parse(
+                &tag_header,
+                &mut BufStream::new(&data),
+                &DefaultFrameParser { strict: true },
+            )
+            .unwrap();
+
+            if let ParsedFrame::Frame(parsed, parsed_flags) = parsed {
+                assert_eq!(parsed.to_string(), frame.to_string());
+                assert_eq!(parsed_flags, flags);
+            } else {
+                panic!("frame was not parsed");
+            }
+        }
+    }
+
     #[test]
     fn parse_unknown_v2() {
         let data = b"ABC\x00\x00\x04\x16\x16\x16\x16";
@@ -2,7 +2,7 @@
 
 use crate::core::io::BufStream;
 use crate::id3v2::frames::{Frame, FrameId};
-use crate::id3v2::{ParseError, ParseResult, TagHeader};
+use crate::id3v2::{MalformedReason, ParseError, ParseResult, TagHeader};
 use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
 use std::io;
@@ -35,15 +35,15 @@ impl RelativeVolumeFrame {
 
         if bits == 0 {
             // Fields must have at least 1 bit.
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadFrameData));
         }
 
         // Once again, the spec says NOTHING about what units the volume fields are supposed to represent,
         // or even if they're floats or not. As a result, we just read plain 64-bit values. This is not
-        // ideal, as it means that we can't upgrade to RVA2/EQU2 in a sane way, but its the only thing
+        // ideal, as it means that upgrading to RVA2/EQU2 has to assume a unit, but its the only thing
         // we can do sadly.
 
-        let len = usize::min((usize::from(bits) + 7) / 8, 8);
+        let len = usize::min(usize::from(bits).div_ceil(8), 8);
 
         // Since the sign of an adjustment is separate from the actual data, we will use an enum instead
         // of a signed integer so that we don't lose information.
@@ -168,12 +168,12 @@ impl EqualizationFrame {
 
         // Bits cannot be zero.
         if bits == 0 {
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadFrameData));
         }
 
         // Begin parsing our adjustments.
         let mut adjustments = BTreeMap::new();
-        let len = usize::min((usize::from(bits) + 7) / 8, 8);
+        let len = usize::min(usize::from(bits).div_ceil(8), 8);
 
         while !stream.is_empty() {
             // EQUA frequencies are special in that the last bit is used as the
@@ -259,7 +259,7 @@ impl Display for EqualizationFrame {
 /// between 0 and 32767hz. All other values will be rounded to the closest valid
 /// value.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
-pub struct Frequency(u16);
+pub struct Frequency(pub(crate) u16);
 
 impl Display for Frequency {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -3,7 +3,7 @@
 use crate::core::io::BufStream;
 use crate::core::string::{self, Encoding};
 use crate::id3v2::frames::{Frame, FrameId};
-use crate::id3v2::{ParseError, ParseResult, TagHeader};
+use crate::id3v2::{MalformedReason, ParseError, ParseResult, TagHeader};
 use std::fmt::{self, Display, Formatter};
 
 #[derive(Default, Debug, Clone)]
@@ -143,7 +143,7 @@ impl PodcastFrame {
         // being a podcast, meaning that this frames existence is pretty much the only form of
         // mutability it has. Just validate the given data and move on.
         if stream.take_rest() != b"\0\0\0\0" {
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadFrameData));
         }
 
         Ok(PodcastFrame)
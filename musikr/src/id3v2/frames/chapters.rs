@@ -64,7 +64,7 @@ impl Frame for ChapterFrame {
         result.extend(self.time.end_time.to_be_bytes());
         result.extend(self.time.start_offset.to_be_bytes());
         result.extend(self.time.end_offset.to_be_bytes());
-        result.extend(self.frames.render(tag_header));
+        result.extend(self.frames.render_embedded(tag_header));
 
         result
     }
@@ -213,7 +213,7 @@ impl Frame for TableOfContentsFrame {
             ))
         }
 
-        result.extend(self.frames.render(tag_header));
+        result.extend(self.frames.render_embedded(tag_header));
 
         result
     }
@@ -269,7 +269,7 @@ fn parse_embedded_frames(
 
     while let Ok(parsed) = frames::parse(tag_header, stream, handler) {
         match parsed {
-            ParsedFrame::Frame(frame) => frames.add_boxed(frame),
+            ParsedFrame::Frame(frame, flags) => frames.add_with_flags(frame, flags),
             ParsedFrame::Unknown(unknown) => {
                 // Drop unknown frames if they're encountered. This is mostly for simplicity, as this
                 // allows all members in a ChapterFrame/TableOfContentsFrame to be public and also
@@ -1,7 +1,7 @@
 use crate::core::io::BufStream;
 use crate::core::string::Encoding;
 use crate::id3v2::tag::Version;
-use crate::id3v2::{ParseError, ParseResult};
+use crate::id3v2::{MalformedReason, ParseError, ParseResult};
 use log::{error, info};
 
 const FLAG_LATIN1: u8 = 0x00;
@@ -17,7 +17,7 @@ pub fn parse(stream: &mut BufStream) -> ParseResult<Encoding> {
         FLAG_UTF8 => Ok(Encoding::Utf8),
         enc => {
             error!("unrecognized encoding {}", enc);
-            Err(ParseError::MalformedData)
+            Err(ParseError::malformed(MalformedReason::InvalidEncodingByte))
         }
     }
 }
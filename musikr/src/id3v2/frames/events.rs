@@ -98,8 +98,9 @@ impl Display for Event {
 }
 
 byte_enum! {
-    #[derive(Ord, PartialOrd)]
+    #[derive(Default, Ord, PartialOrd)]
     pub enum EventType {
+        #[default]
         Padding = 0x00,
         EndOfInitialSilence = 0x01,
         IntroStart = 0x02,
@@ -145,12 +146,6 @@ byte_enum! {
     EventType::Padding
 }
 
-impl Default for EventType {
-    fn default() -> Self {
-        EventType::Padding
-    }
-}
-
 #[derive(Clone, Debug)]
 pub struct SyncedTempoCodesFrame {
     format: TimestampFormat,
@@ -265,23 +260,19 @@ byte_enum! {
     /// The timestamp format represents the units for any timestamps
     /// in an ID3v2 frame. For the best compatibility with programs,
     /// [`Millis`](TimestampFormat::Millis) should be used.
+    #[derive(Default)]
     pub enum TimestampFormat {
         /// No unit was specified.
         Other = 0x00,
         /// Timestamps are in MPEG Frames.
         MpegFrames = 0x01,
         /// Timestamps are in milliseconds.
+        #[default]
         Millis = 0x02,
     };
     TimestampFormat::Other
 }
 
-impl Default for TimestampFormat {
-    fn default() -> Self {
-        TimestampFormat::Millis
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,10 +113,12 @@ impl Display for AttachedPictureFrame {
 }
 
 byte_enum! {
+    #[derive(Default)]
     pub enum PictureType {
         Other = 0x00,
         FileIcon = 0x01,
         OtherFileIcon = 0x02,
+        #[default]
         FrontCover = 0x03,
         BackCover = 0x04,
         LeafletPage = 0x05,
@@ -139,12 +141,6 @@ byte_enum! {
     PictureType::Other
 }
 
-impl Default for PictureType {
-    fn default() -> Self {
-        PictureType::FrontCover
-    }
-}
-
 #[derive(Default, Debug, Clone)]
 pub struct GeneralObjectFrame {
     pub encoding: Encoding,
@@ -0,0 +1,164 @@
+//! Frames that register symbols for use by other frames.
+
+use crate::core::io::BufStream;
+use crate::core::string::{self, Encoding};
+use crate::id3v2::frames::{Frame, FrameId};
+use crate::id3v2::{ParseResult, TagHeader};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Default, Debug, Clone)]
+pub struct EncryptionRegistrationFrame {
+    pub owner: String,
+    /// The method symbol that encrypted frames will refer to. Only values above
+    /// `0x80` are allowed by the spec.
+    pub method: u8,
+    pub data: Vec<u8>,
+}
+
+impl EncryptionRegistrationFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let owner = string::read_terminated(Encoding::Latin1, stream);
+        let method = stream.read_u8()?;
+        let data = stream.take_rest().to_vec();
+
+        Ok(Self {
+            owner,
+            method,
+            data,
+        })
+    }
+}
+
+impl Frame for EncryptionRegistrationFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"ENCR")
+    }
+
+    fn key(&self) -> String {
+        format!["ENCR:{}", self.owner]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.owner.is_empty()
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(string::render_terminated(Encoding::Latin1, &self.owner));
+        result.push(self.method);
+        result.extend(&self.data);
+
+        result
+    }
+}
+
+impl Display for EncryptionRegistrationFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}", self.owner]
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct GroupIdFrame {
+    pub owner: String,
+    /// The group symbol that grouped frames will refer to. Only values above
+    /// `0x80` are allowed by the spec.
+    pub group: u8,
+    pub data: Vec<u8>,
+}
+
+impl GroupIdFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let owner = string::read_terminated(Encoding::Latin1, stream);
+        let group = stream.read_u8()?;
+        let data = stream.take_rest().to_vec();
+
+        Ok(Self { owner, group, data })
+    }
+}
+
+impl Frame for GroupIdFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"GRID")
+    }
+
+    fn key(&self) -> String {
+        format!["GRID:{}", self.owner]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.owner.is_empty()
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(string::render_terminated(Encoding::Latin1, &self.owner));
+        result.push(self.group);
+        result.extend(&self.data);
+
+        result
+    }
+}
+
+impl Display for GroupIdFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}", self.owner]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCR_DATA: &[u8] = b"ENCR\x00\x00\x00\x19\x00\x00\
+                               https://musikr.org\0\
+                               \x81\
+                               \x16\x16\x16\x16\x16";
+
+    const GRID_DATA: &[u8] = b"GRID\x00\x00\x00\x18\x00\x00\
+                               https://musikr.org\0\
+                               \x80\
+                               \x01\x02\x03\x04";
+
+    #[test]
+    fn parse_encr() {
+        make_frame!(EncryptionRegistrationFrame, ENCR_DATA, frame);
+
+        assert_eq!(frame.owner, "https://musikr.org");
+        assert_eq!(frame.method, 0x81);
+        assert_eq!(frame.data, b"\x16\x16\x16\x16\x16");
+    }
+
+    #[test]
+    fn parse_grid() {
+        make_frame!(GroupIdFrame, GRID_DATA, frame);
+
+        assert_eq!(frame.owner, "https://musikr.org");
+        assert_eq!(frame.group, 0x80);
+        assert_eq!(frame.data, b"\x01\x02\x03\x04");
+    }
+
+    #[test]
+    fn render_encr() {
+        let frame = EncryptionRegistrationFrame {
+            owner: String::from("https://musikr.org"),
+            method: 0x81,
+            data: b"\x16\x16\x16\x16\x16".to_vec(),
+        };
+
+        assert_render!(frame, ENCR_DATA);
+    }
+
+    #[test]
+    fn render_grid() {
+        let frame = GroupIdFrame {
+            owner: String::from("https://musikr.org"),
+            group: 0x80,
+            data: b"\x01\x02\x03\x04".to_vec(),
+        };
+
+        assert_render!(frame, GRID_DATA);
+    }
+}
@@ -0,0 +1,509 @@
+//! Frames for seeking within the audio stream.
+
+use crate::core::io::BufStream;
+use crate::id3v2::frames::{Frame, FrameId};
+use crate::id3v2::{MalformedReason, ParseError, ParseResult, TagHeader};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Default, Debug, Clone)]
+pub struct MpegLookupFrame {
+    /// The amount of MPEG frames between each reference. Capped at 16 bits.
+    pub frames: u16,
+    /// The amount of bytes between each reference. Capped at 24 bits.
+    pub bytes: u32,
+    /// The amount of milliseconds between each reference. Capped at 24 bits.
+    pub millis: u32,
+    /// The bit width of [`MpegReference::bytes_dev`](MpegReference::bytes_dev).
+    pub bytes_bits: u8,
+    /// The bit width of [`MpegReference::millis_dev`](MpegReference::millis_dev).
+    pub millis_bits: u8,
+    pub refs: Vec<MpegReference>,
+}
+
+impl MpegLookupFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let frames = stream.read_be_u16()?;
+        let bytes = read_u24(stream)?;
+        let millis = read_u24(stream)?;
+        let bytes_bits = stream.read_u8()?;
+        let millis_bits = stream.read_u8()?;
+
+        // The spec leaves the deviation widths unbounded, but anything that does not fit
+        // in a u32 is nonsensical for files that are limited to 32-bit sizes anyway.
+        if bytes_bits > 32 || millis_bits > 32 {
+            return Err(ParseError::malformed(MalformedReason::BadFrameData));
+        }
+
+        let ref_bits = (bytes_bits + millis_bits) as usize;
+        let mut refs = Vec::new();
+
+        if ref_bits > 0 {
+            let mut reader = BitReader::new(stream.take_rest());
+
+            // Any trailing bits that can't form a full reference are just padding.
+            while reader.remaining() >= ref_bits {
+                let bytes_dev = reader.read(bytes_bits);
+                let millis_dev = reader.read(millis_bits);
+
+                refs.push(MpegReference {
+                    bytes_dev,
+                    millis_dev,
+                });
+            }
+        }
+
+        Ok(Self {
+            frames,
+            bytes,
+            millis,
+            bytes_bits,
+            millis_bits,
+            refs,
+        })
+    }
+}
+
+impl Frame for MpegLookupFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"MLLT")
+    }
+
+    fn key(&self) -> String {
+        String::from("MLLT")
+    }
+
+    fn is_empty(&self) -> bool {
+        self.refs.is_empty()
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(self.frames.to_be_bytes());
+        result.extend(&self.bytes.to_be_bytes()[1..]);
+        result.extend(&self.millis.to_be_bytes()[1..]);
+
+        let bytes_bits = u8::min(self.bytes_bits, 32);
+        let millis_bits = u8::min(self.millis_bits, 32);
+
+        result.push(bytes_bits);
+        result.push(millis_bits);
+
+        let mut writer = BitWriter::new(result);
+
+        for reference in &self.refs {
+            writer.write(reference.bytes_dev, bytes_bits);
+            writer.write(reference.millis_dev, millis_bits);
+        }
+
+        // Any unused bits in the last byte are left as zero padding.
+        writer.data
+    }
+}
+
+impl Display for MpegLookupFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![
+            f,
+            "{} frames, {} bytes, {}ms",
+            self.frames, self.bytes, self.millis
+        ]
+    }
+}
+
+/// A single deviation entry in a [`MpegLookupFrame`](MpegLookupFrame).
+///
+/// Each deviation is relative to the fixed intervals specified in the frame, and
+/// will be truncated to the bit widths specified in the frame when rendered.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct MpegReference {
+    pub bytes_dev: u32,
+    pub millis_dev: u32,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SeekFrame {
+    /// The minimum offset from the end of this tag to the start of the next tag.
+    pub offset: u32,
+}
+
+impl SeekFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let offset = stream.read_be_u32()?;
+
+        Ok(Self { offset })
+    }
+}
+
+impl Frame for SeekFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"SEEK")
+    }
+
+    fn key(&self) -> String {
+        String::from("SEEK")
+    }
+
+    fn is_empty(&self) -> bool {
+        false // Can never be empty
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        self.offset.to_be_bytes().to_vec()
+    }
+}
+
+impl Display for SeekFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}", self.offset]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioSeekIndexFrame {
+    /// The offset of the indexed audio data, counted from the start of the file.
+    pub start: u32,
+    /// The length of the indexed audio data, in bytes.
+    pub len: u32,
+    /// The bit width of each index point. Only 8 and 16 are valid. Points that do not fit
+    /// in 8 bits will always be written as 16 bits.
+    pub bits: u8,
+    /// The index points, each being the numerator of a fraction of `len` with a
+    /// denominator of `2^bits`. The number of points is the length of this list.
+    pub points: Vec<u16>,
+}
+
+impl AudioSeekIndexFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let start = stream.read_be_u32()?;
+        let len = stream.read_be_u32()?;
+        let count = stream.read_be_u16()?;
+        let bits = stream.read_u8()?;
+
+        let mut points = Vec::with_capacity(count as usize);
+
+        for _ in 0..count {
+            let point = match bits {
+                8 => stream.read_u8()?.into(),
+                16 => stream.read_be_u16()?,
+                _ => return Err(ParseError::malformed(MalformedReason::BadFrameData)),
+            };
+
+            points.push(point);
+        }
+
+        Ok(Self {
+            start,
+            len,
+            bits,
+            points,
+        })
+    }
+
+    /// Moves the start of the indexed data by `delta` bytes.
+    ///
+    /// This is done automatically when a tag is saved and the audio data moves, but
+    /// must be done manually if the audio is moved by other means. The index points
+    /// are relative to the indexed data and are left untouched.
+    pub fn shift(&mut self, delta: i64) {
+        self.start = (self.start as i64 + delta).clamp(0, u32::MAX as i64) as u32;
+    }
+}
+
+impl Frame for AudioSeekIndexFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"ASPI")
+    }
+
+    fn key(&self) -> String {
+        String::from("ASPI")
+    }
+
+    fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(self.start.to_be_bytes());
+        result.extend(self.len.to_be_bytes());
+
+        // There can only be 65535 index points, drop any extra.
+        let count = usize::min(self.points.len(), u16::MAX as usize);
+        result.extend((count as u16).to_be_bytes());
+
+        // Any invalid bit width is treated as 16, as that loses no information. The same goes
+        // for 8-bit indexes with points that are too wide.
+        let points = &self.points[..count];
+
+        if self.bits == 8 && points.iter().all(|&point| point <= u8::MAX as u16) {
+            result.push(8);
+            result.extend(points.iter().map(|&point| point as u8));
+        } else {
+            result.push(16);
+
+            for point in points {
+                result.extend(point.to_be_bytes());
+            }
+        }
+
+        result
+    }
+}
+
+impl Default for AudioSeekIndexFrame {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            len: 0,
+            bits: 16,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl Display for AudioSeekIndexFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{} points", self.points.len()]
+    }
+}
+
+pub(super) fn read_u24(stream: &mut BufStream) -> ParseResult<u32> {
+    let mut bytes = [0; 4];
+    stream.read_exact(&mut bytes[1..4])?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    fn read(&mut self, bits: u8) -> u32 {
+        let mut value = 0;
+
+        for _ in 0..bits {
+            let bit = (self.data[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.pos += 1;
+        }
+
+        value
+    }
+}
+
+struct BitWriter {
+    data: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn new(data: Vec<u8>) -> Self {
+        Self { data, bits: 0 }
+    }
+
+    fn write(&mut self, value: u32, bits: u8) {
+        for i in (0..bits).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.data.push(0);
+            }
+
+            let bit = ((value >> i) & 1) as u8;
+            *self.data.last_mut().unwrap() |= bit << (7 - self.bits % 8);
+            self.bits += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id3v2::tag::Version;
+    use crate::id3v2::Malformed;
+
+    const MLLT_DATA: &[u8] = b"MLLT\x00\x00\x00\x0D\x00\x00\
+                               \x00\x10\
+                               \x00\x04\x00\
+                               \x00\x00\x1A\
+                               \x04\x08\
+                               \xA1\x23\xFF";
+
+    const MLLT_DATA_V2: &[u8] = b"MLL\x00\x00\x0D\
+                                  \x00\x10\
+                                  \x00\x04\x00\
+                                  \x00\x00\x1A\
+                                  \x04\x08\
+                                  \xA1\x23\xFF";
+
+    const SEEK_DATA: &[u8] = b"SEEK\x00\x00\x00\x04\x00\x00\
+                               \x00\x00\x12\x34";
+
+    const ASPI_DATA: &[u8] = b"ASPI\x00\x00\x00\x13\x00\x00\
+                               \x00\x00\x04\x00\
+                               \x00\x01\x00\x00\
+                               \x00\x04\
+                               \x10\
+                               \x00\x00\x40\x00\x80\x00\xC0\x00";
+
+    const ASPI_DATA_8BIT: &[u8] = b"ASPI\x00\x00\x00\x0F\x00\x00\
+                                    \x00\x00\x04\x00\
+                                    \x00\x01\x00\x00\
+                                    \x00\x04\
+                                    \x08\
+                                    \x00\x40\x80\xC0";
+
+    fn mllt_frame() -> MpegLookupFrame {
+        MpegLookupFrame {
+            frames: 16,
+            bytes: 1024,
+            millis: 26,
+            bytes_bits: 4,
+            millis_bits: 8,
+            refs: vec![
+                MpegReference {
+                    bytes_dev: 0xA,
+                    millis_dev: 0x12,
+                },
+                MpegReference {
+                    bytes_dev: 0x3,
+                    millis_dev: 0xFF,
+                },
+            ],
+        }
+    }
+
+    fn assert_mllt(frame: &MpegLookupFrame) {
+        let expected = mllt_frame();
+
+        assert_eq!(frame.frames, expected.frames);
+        assert_eq!(frame.bytes, expected.bytes);
+        assert_eq!(frame.millis, expected.millis);
+        assert_eq!(frame.bytes_bits, expected.bytes_bits);
+        assert_eq!(frame.millis_bits, expected.millis_bits);
+        assert_eq!(frame.refs, expected.refs);
+    }
+
+    #[test]
+    fn parse_mllt() {
+        make_frame!(MpegLookupFrame, MLLT_DATA, frame);
+        assert_mllt(frame);
+    }
+
+    #[test]
+    fn parse_mllt_v3() {
+        make_frame!(MpegLookupFrame, MLLT_DATA, Version::V23, frame);
+        assert_mllt(frame);
+    }
+
+    #[test]
+    fn parse_mllt_v2() {
+        make_frame!(MpegLookupFrame, MLLT_DATA_V2, Version::V22, frame);
+        assert_mllt(frame);
+    }
+
+    #[test]
+    fn parse_mllt_too_wide() {
+        let data = b"\x00\x10\x00\x04\x00\x00\x00\x1A\x21\x08\xFF\xFF\xFF\xFF\xFF";
+
+        assert!(matches!(
+            MpegLookupFrame::parse(&mut BufStream::new(data)),
+            Err(ParseError::MalformedData(Malformed {
+                reason: MalformedReason::BadFrameData,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn render_mllt() {
+        assert_render!(mllt_frame(), MLLT_DATA);
+    }
+
+    #[test]
+    fn parse_seek() {
+        make_frame!(SeekFrame, SEEK_DATA, frame);
+        assert_eq!(frame.offset, 0x1234);
+    }
+
+    #[test]
+    fn render_seek() {
+        assert_render!(SeekFrame { offset: 0x1234 }, SEEK_DATA);
+    }
+
+    #[test]
+    fn parse_aspi() {
+        make_frame!(AudioSeekIndexFrame, ASPI_DATA, frame);
+
+        assert_eq!(frame.start, 0x400);
+        assert_eq!(frame.len, 0x10000);
+        assert_eq!(frame.bits, 16);
+        assert_eq!(frame.points, vec![0x0000, 0x4000, 0x8000, 0xC000]);
+    }
+
+    #[test]
+    fn parse_aspi_8bit() {
+        make_frame!(AudioSeekIndexFrame, ASPI_DATA_8BIT, frame);
+
+        assert_eq!(frame.bits, 8);
+        assert_eq!(frame.points, vec![0x00, 0x40, 0x80, 0xC0]);
+    }
+
+    #[test]
+    fn render_aspi() {
+        let frame = AudioSeekIndexFrame {
+            start: 0x400,
+            len: 0x10000,
+            bits: 16,
+            points: vec![0x0000, 0x4000, 0x8000, 0xC000],
+        };
+
+        assert_render!(frame, ASPI_DATA);
+    }
+
+    #[test]
+    fn render_aspi_8bit() {
+        let frame = AudioSeekIndexFrame {
+            start: 0x400,
+            len: 0x10000,
+            bits: 8,
+            points: vec![0x00, 0x40, 0x80, 0xC0],
+        };
+
+        assert_render!(frame, ASPI_DATA_8BIT);
+    }
+
+    #[test]
+    fn render_aspi_8bit_too_wide() {
+        let frame = AudioSeekIndexFrame {
+            start: 0x400,
+            len: 0x10000,
+            bits: 8,
+            points: vec![0x0000, 0x4000, 0x8000, 0xC000],
+        };
+
+        assert_render!(frame, ASPI_DATA);
+    }
+
+    #[test]
+    fn shift_aspi() {
+        let mut frame = AudioSeekIndexFrame {
+            start: 0x400,
+            ..Default::default()
+        };
+
+        frame.shift(0x100);
+        assert_eq!(frame.start, 0x500);
+        frame.shift(-0x600);
+        assert_eq!(frame.start, 0);
+    }
+}
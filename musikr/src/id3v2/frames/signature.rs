@@ -0,0 +1,81 @@
+//! Frames that sign groups of frames.
+
+use crate::core::io::BufStream;
+use crate::id3v2::frames::{Frame, FrameId};
+use crate::id3v2::{ParseResult, TagHeader};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Default, Debug, Clone)]
+pub struct SignatureFrame {
+    /// The group symbol of the frames that are signed, as registered in a
+    /// [`GroupIdFrame`](crate::id3v2::frames::GroupIdFrame).
+    pub group: u8,
+    pub signature: Vec<u8>,
+}
+
+impl SignatureFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let group = stream.read_u8()?;
+        let signature = stream.take_rest().to_vec();
+
+        Ok(Self { group, signature })
+    }
+}
+
+impl Frame for SignatureFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"SIGN")
+    }
+
+    fn key(&self) -> String {
+        format!["SIGN:{}", self.group]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.signature.is_empty()
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = vec![self.group];
+        result.extend(&self.signature);
+        result
+    }
+}
+
+impl Display for SignatureFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for byte in &self.signature {
+            write![f, "{:02x}", byte]?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGN_DATA: &[u8] = b"SIGN\x00\x00\x00\x09\x00\x00\
+                               \x80\
+                               \xDE\xAD\xBE\xEF\x01\x23\x45\x67";
+
+    #[test]
+    fn parse_sign() {
+        make_frame!(SignatureFrame, SIGN_DATA, frame);
+
+        assert_eq!(frame.group, 0x80);
+        assert_eq!(frame.signature, b"\xDE\xAD\xBE\xEF\x01\x23\x45\x67");
+        assert_eq!(frame.to_string(), "deadbeef01234567");
+    }
+
+    #[test]
+    fn render_sign() {
+        let frame = SignatureFrame {
+            group: 0x80,
+            signature: b"\xDE\xAD\xBE\xEF\x01\x23\x45\x67".to_vec(),
+        };
+
+        assert_render!(frame, SIGN_DATA);
+    }
+}
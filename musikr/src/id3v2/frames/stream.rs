@@ -0,0 +1,569 @@
+//! Frames that describe how the audio stream is played back and delivered.
+
+use crate::core::io::BufStream;
+use crate::core::string::{self, Encoding};
+use crate::id3v2::frames::events::TimestampFormat;
+use crate::id3v2::frames::seek::read_u24;
+use crate::id3v2::frames::{Frame, FrameId};
+use crate::id3v2::{compat, MalformedReason, ParseError, ParseResult, TagHeader};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Default, Debug, Clone)]
+pub struct ReverbFrame {
+    /// The delay between bounces on the left channel, in milliseconds.
+    pub left: u16,
+    /// The delay between bounces on the right channel, in milliseconds.
+    pub right: u16,
+    pub bounces_left: u8,
+    pub bounces_right: u8,
+    pub feedback_ll: u8,
+    pub feedback_lr: u8,
+    pub feedback_rr: u8,
+    pub feedback_rl: u8,
+    pub premix_lr: u8,
+    pub premix_rl: u8,
+}
+
+impl ReverbFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        Ok(Self {
+            left: stream.read_be_u16()?,
+            right: stream.read_be_u16()?,
+            bounces_left: stream.read_u8()?,
+            bounces_right: stream.read_u8()?,
+            feedback_ll: stream.read_u8()?,
+            feedback_lr: stream.read_u8()?,
+            feedback_rr: stream.read_u8()?,
+            feedback_rl: stream.read_u8()?,
+            premix_lr: stream.read_u8()?,
+            premix_rl: stream.read_u8()?,
+        })
+    }
+}
+
+impl Frame for ReverbFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"RVRB")
+    }
+
+    fn key(&self) -> String {
+        String::from("RVRB")
+    }
+
+    fn is_empty(&self) -> bool {
+        false // Can never be empty
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(self.left.to_be_bytes());
+        result.extend(self.right.to_be_bytes());
+        result.extend([
+            self.bounces_left,
+            self.bounces_right,
+            self.feedback_ll,
+            self.feedback_lr,
+            self.feedback_rr,
+            self.feedback_rl,
+            self.premix_lr,
+            self.premix_rl,
+        ]);
+
+        result
+    }
+}
+
+impl Display for ReverbFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}ms, {}ms", self.left, self.right]
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct BufferSizeFrame {
+    /// The recommended buffer size, in bytes. Capped at 24 bits.
+    pub size: u32,
+    /// Whether [`AudioEncryptionFrame`](crate::id3v2::frames::AudioEncryptionFrame)s and
+    /// other ID3v2 tags may be embedded within the audio stream.
+    pub embedded: bool,
+    /// The offset from the end of this tag to the next embedded tag, if any.
+    pub offset: Option<u32>,
+}
+
+impl BufferSizeFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let size = read_u24(stream)?;
+        let embedded = stream.read_u8()? & 0x1 != 0;
+
+        // The offset is optional, but anything other than a full offset is malformed.
+        let offset = match stream.remaining() {
+            0 => None,
+            4 => Some(stream.read_be_u32()?),
+            _ => return Err(ParseError::malformed(MalformedReason::UnexpectedEnd)),
+        };
+
+        Ok(Self {
+            size,
+            embedded,
+            offset,
+        })
+    }
+}
+
+impl Frame for BufferSizeFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"RBUF")
+    }
+
+    fn key(&self) -> String {
+        String::from("RBUF")
+    }
+
+    fn is_empty(&self) -> bool {
+        false // Can never be empty
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(&self.size.to_be_bytes()[1..]);
+        result.push(self.embedded as u8);
+
+        if let Some(offset) = self.offset {
+            result.extend(offset.to_be_bytes());
+        }
+
+        result
+    }
+}
+
+impl Display for BufferSizeFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{} bytes", self.size]
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct PositionSyncFrame {
+    pub format: TimestampFormat,
+    /// The position in the audio where this tag was received, in units of `format`.
+    pub position: u32,
+}
+
+impl PositionSyncFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let format = TimestampFormat::parse(stream.read_u8()?);
+        let position = stream.read_be_u32()?;
+
+        Ok(Self { format, position })
+    }
+}
+
+impl Frame for PositionSyncFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"POSS")
+    }
+
+    fn key(&self) -> String {
+        String::from("POSS")
+    }
+
+    fn is_empty(&self) -> bool {
+        false // Can never be empty
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut data = vec![self.format as u8];
+        data.extend(self.position.to_be_bytes());
+        data
+    }
+}
+
+impl Display for PositionSyncFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}", self.position]
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct AudioEncryptionFrame {
+    pub owner: String,
+    /// The start of the unencrypted preview, in MPEG frames.
+    pub preview_start: u16,
+    /// The length of the unencrypted preview, in MPEG frames.
+    pub preview_len: u16,
+    pub data: Vec<u8>,
+}
+
+impl AudioEncryptionFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        let owner = string::read_terminated(Encoding::Latin1, stream);
+        let preview_start = stream.read_be_u16()?;
+        let preview_len = stream.read_be_u16()?;
+        let data = stream.take_rest().to_vec();
+
+        Ok(Self {
+            owner,
+            preview_start,
+            preview_len,
+            data,
+        })
+    }
+}
+
+impl Frame for AudioEncryptionFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"AENC")
+    }
+
+    fn key(&self) -> String {
+        format!["AENC:{}", self.owner]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.owner.is_empty()
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(string::render_terminated(Encoding::Latin1, &self.owner));
+        result.extend(self.preview_start.to_be_bytes());
+        result.extend(self.preview_len.to_be_bytes());
+        result.extend(&self.data);
+
+        result
+    }
+}
+
+impl Display for AudioEncryptionFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}", self.owner]
+    }
+}
+
+/// A link to a frame that is stored in another location.
+///
+/// The linked frame is identified by the [`frame_id`](LinkedInfoFrame::frame_id) and any
+/// additional [`data`](LinkedInfoFrame::data) that the frame would need to be uniquely
+/// identified, such as the language of a [`CommentsFrame`](crate::id3v2::frames::CommentsFrame).
+#[derive(Debug, Clone)]
+pub struct LinkedInfoFrame {
+    /// The ID of the linked frame.
+    pub frame_id: FrameId,
+    /// The location of the linked frame.
+    pub url: String,
+    /// Additional data needed to identify the linked frame.
+    pub data: Vec<String>,
+}
+
+impl LinkedInfoFrame {
+    pub(crate) fn parse(stream: &mut BufStream) -> ParseResult<Self> {
+        // The ID3v2.3 spec claims that the frame ID here is only 3 bytes, but that is almost
+        // certainly a holdover from ID3v2.2. In practice, every tagger writes 4 bytes.
+        let frame_id = match FrameId::try_new(&stream.read_array()?) {
+            Ok(id) => id,
+            Err(_) => return Err(ParseError::malformed(MalformedReason::BadFrameData)),
+        };
+
+        Self::parse_rest(frame_id, stream)
+    }
+
+    pub(crate) fn parse_v2(stream: &mut BufStream) -> ParseResult<Self> {
+        // ID3v2.2 frames link to other ID3v2.2 frames, so the ID has to be upgraded too.
+        let frame_id = match &stream.read_array()? {
+            b"PIC" => FrameId::new(b"APIC"),
+            v2_id => compat::upgrade_v2_id(v2_id)?,
+        };
+
+        Self::parse_rest(frame_id, stream)
+    }
+
+    fn parse_rest(frame_id: FrameId, stream: &mut BufStream) -> ParseResult<Self> {
+        let url = string::read_terminated(Encoding::Latin1, stream);
+        let mut data = Vec::new();
+
+        while !stream.is_empty() {
+            data.push(string::read_terminated(Encoding::Latin1, stream));
+        }
+
+        Ok(Self {
+            frame_id,
+            url,
+            data,
+        })
+    }
+}
+
+impl Frame for LinkedInfoFrame {
+    fn id(&self) -> FrameId {
+        FrameId::new(b"LINK")
+    }
+
+    fn key(&self) -> String {
+        if self.data.is_empty() {
+            format!["LINK:{}:{}", self.frame_id, self.url]
+        } else {
+            format![
+                "LINK:{}:{}:{}",
+                self.frame_id,
+                self.url,
+                self.data.join(":")
+            ]
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.url.is_empty()
+    }
+
+    fn render(&self, _: &TagHeader) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        result.extend(self.frame_id.as_ref());
+        result.extend(string::render_terminated(Encoding::Latin1, &self.url));
+
+        for (i, data) in self.data.iter().enumerate() {
+            if i < self.data.len() - 1 {
+                result.extend(string::render_terminated(Encoding::Latin1, data));
+            } else {
+                result.extend(string::render(Encoding::Latin1, data));
+            }
+        }
+
+        result
+    }
+}
+
+impl Display for LinkedInfoFrame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{}", self.url]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id3v2::tag::Version;
+    use crate::id3v2::Malformed;
+
+    const RVRB_DATA: &[u8] = b"RVRB\x00\x00\x00\x0C\x00\x00\
+                               \x01\x2C\
+                               \x00\xFA\
+                               \x04\x03\
+                               \x80\x40\x7F\x20\
+                               \x10\x08";
+
+    const RVRB_DATA_V2: &[u8] = b"REV\x00\x00\x0C\
+                                  \x01\x2C\
+                                  \x00\xFA\
+                                  \x04\x03\
+                                  \x80\x40\x7F\x20\
+                                  \x10\x08";
+
+    const RBUF_DATA: &[u8] = b"RBUF\x00\x00\x00\x08\x00\x00\
+                               \x01\x00\x00\
+                               \x01\
+                               \x00\x00\x40\x00";
+
+    const RBUF_DATA_V2: &[u8] = b"BUF\x00\x00\x04\
+                                  \x01\x00\x00\
+                                  \x00";
+
+    const POSS_DATA: &[u8] = b"POSS\x00\x00\x00\x05\x00\x00\
+                               \x01\
+                               \x00\x00\x12\x34";
+
+    const AENC_DATA: &[u8] = b"AENC\x00\x00\x00\x1B\x00\x00\
+                               https://musikr.org\0\
+                               \x00\x10\
+                               \x01\x00\
+                               \x16\x16\x16\x16";
+
+    const AENC_DATA_V2: &[u8] = b"CRA\x00\x00\x1B\
+                                  https://musikr.org\0\
+                                  \x00\x10\
+                                  \x01\x00\
+                                  \x16\x16\x16\x16";
+
+    const LINK_DATA: &[u8] = b"LINK\x00\x00\x00\x1A\x00\x00\
+                               COMM\
+                               https://musikr.org\0\
+                               eng";
+
+    const LINK_DATA_V2: &[u8] = b"LNK\x00\x00\x19\
+                                  COM\
+                                  https://musikr.org\0\
+                                  eng";
+
+    fn rvrb_frame() -> ReverbFrame {
+        ReverbFrame {
+            left: 300,
+            right: 250,
+            bounces_left: 4,
+            bounces_right: 3,
+            feedback_ll: 0x80,
+            feedback_lr: 0x40,
+            feedback_rr: 0x7F,
+            feedback_rl: 0x20,
+            premix_lr: 0x10,
+            premix_rl: 0x08,
+        }
+    }
+
+    fn assert_rvrb(frame: &ReverbFrame) {
+        let expected = rvrb_frame();
+
+        assert_eq!(frame.left, expected.left);
+        assert_eq!(frame.right, expected.right);
+        assert_eq!(frame.bounces_left, expected.bounces_left);
+        assert_eq!(frame.bounces_right, expected.bounces_right);
+        assert_eq!(frame.feedback_ll, expected.feedback_ll);
+        assert_eq!(frame.feedback_lr, expected.feedback_lr);
+        assert_eq!(frame.feedback_rr, expected.feedback_rr);
+        assert_eq!(frame.feedback_rl, expected.feedback_rl);
+        assert_eq!(frame.premix_lr, expected.premix_lr);
+        assert_eq!(frame.premix_rl, expected.premix_rl);
+    }
+
+    #[test]
+    fn parse_rvrb() {
+        make_frame!(ReverbFrame, RVRB_DATA, frame);
+        assert_rvrb(frame);
+        assert_eq!(frame.to_string(), "300ms, 250ms");
+    }
+
+    #[test]
+    fn parse_rvrb_v2() {
+        make_frame!(ReverbFrame, RVRB_DATA_V2, Version::V22, frame);
+        assert_rvrb(frame);
+    }
+
+    #[test]
+    fn render_rvrb() {
+        assert_render!(rvrb_frame(), RVRB_DATA);
+    }
+
+    #[test]
+    fn parse_rbuf() {
+        make_frame!(BufferSizeFrame, RBUF_DATA, frame);
+
+        assert_eq!(frame.size, 0x10000);
+        assert!(frame.embedded);
+        assert_eq!(frame.offset, Some(0x4000));
+    }
+
+    #[test]
+    fn parse_rbuf_v2() {
+        make_frame!(BufferSizeFrame, RBUF_DATA_V2, Version::V22, frame);
+
+        assert_eq!(frame.size, 0x10000);
+        assert!(!frame.embedded);
+        assert_eq!(frame.offset, None);
+    }
+
+    #[test]
+    fn parse_rbuf_truncated_offset() {
+        let data = b"\x01\x00\x00\x01\x00\x40";
+
+        assert!(matches!(
+            BufferSizeFrame::parse(&mut BufStream::new(data)),
+            Err(ParseError::MalformedData(Malformed {
+                reason: MalformedReason::UnexpectedEnd,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn render_rbuf() {
+        let frame = BufferSizeFrame {
+            size: 0x10000,
+            embedded: true,
+            offset: Some(0x4000),
+        };
+
+        assert_render!(frame, RBUF_DATA);
+    }
+
+    #[test]
+    fn parse_poss() {
+        make_frame!(PositionSyncFrame, POSS_DATA, frame);
+
+        assert_eq!(frame.format, TimestampFormat::MpegFrames);
+        assert_eq!(frame.position, 0x1234);
+        assert_eq!(frame.to_string(), "4660");
+    }
+
+    #[test]
+    fn render_poss() {
+        let frame = PositionSyncFrame {
+            format: TimestampFormat::MpegFrames,
+            position: 0x1234,
+        };
+
+        assert_render!(frame, POSS_DATA);
+    }
+
+    fn assert_aenc(frame: &AudioEncryptionFrame) {
+        assert_eq!(frame.owner, "https://musikr.org");
+        assert_eq!(frame.preview_start, 0x10);
+        assert_eq!(frame.preview_len, 0x100);
+        assert_eq!(frame.data, b"\x16\x16\x16\x16");
+    }
+
+    #[test]
+    fn parse_aenc() {
+        make_frame!(AudioEncryptionFrame, AENC_DATA, frame);
+        assert_aenc(frame);
+    }
+
+    #[test]
+    fn parse_aenc_v2() {
+        make_frame!(AudioEncryptionFrame, AENC_DATA_V2, Version::V22, frame);
+        assert_aenc(frame);
+    }
+
+    #[test]
+    fn render_aenc() {
+        let frame = AudioEncryptionFrame {
+            owner: String::from("https://musikr.org"),
+            preview_start: 0x10,
+            preview_len: 0x100,
+            data: b"\x16\x16\x16\x16".to_vec(),
+        };
+
+        assert_render!(frame, AENC_DATA);
+    }
+
+    #[test]
+    fn parse_link() {
+        make_frame!(LinkedInfoFrame, LINK_DATA, frame);
+
+        assert_eq!(frame.frame_id, FrameId::new(b"COMM"));
+        assert_eq!(frame.url, "https://musikr.org");
+        assert_eq!(frame.data, vec![String::from("eng")]);
+        assert_eq!(frame.key(), "LINK:COMM:https://musikr.org:eng");
+    }
+
+    #[test]
+    fn parse_link_v2() {
+        make_frame!(LinkedInfoFrame, LINK_DATA_V2, Version::V22, frame);
+
+        assert_eq!(frame.frame_id, FrameId::new(b"COMM"));
+        assert_eq!(frame.url, "https://musikr.org");
+        assert_eq!(frame.data, vec![String::from("eng")]);
+    }
+
+    #[test]
+    fn render_link() {
+        let frame = LinkedInfoFrame {
+            frame_id: FrameId::new(b"COMM"),
+            url: String::from("https://musikr.org"),
+            data: vec![String::from("eng")],
+        };
+
+        assert_render!(frame, LINK_DATA);
+    }
+}
@@ -5,18 +5,18 @@
 //! couple of details are common across all implementations:
 //!
 //! - Text frames expose an encoding that will be used when the frame is written. More information can be found
-//! in [`Encoding`](crate::core::Encoding).
+//!   in [`Encoding`](crate::core::Encoding).
 //! - A text frame implementation can correspond to multiple Frame IDs.
 //! - A text frame can contain more than one string.
 //!
 //! # Quirks
 //!
 //! - Certain text frames may be an iTunes extension or only exist in a specific ID3v2 version. If this is the case,
-//! then it will be marked accordingly.
+//!   then it will be marked accordingly.
 //! - According to the standard, ID3v2.3 text frames cannot have multiple fields delimited by a null terminator.
-//! While musikr does not enforce this restriction, some taggers might.
+//!   While musikr does not enforce this restriction, some taggers might.
 //! - `UserTextFrame` is not meant to have multiple fields, however the other major tagging libraries all seem to
-//! enable this, so musikr implements it regardless.
+//!   enable this, so musikr implements it regardless.
 
 use crate::core::io::BufStream;
 use crate::core::string::{self, Encoding};
@@ -24,8 +24,9 @@ use crate::id3v2::frames::{encoding, Frame, FrameId};
 use crate::id3v2::{ParseResult, TagHeader};
 use log::{info, warn};
 use std::str::{self, FromStr};
-use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
+use std::ops::Index;
+use std::vec;
 
 /// Specific text-based metadata.
 ///
@@ -41,7 +42,7 @@ use std::fmt::{self, Display, Formatter};
 /// ```text
 /// TALB Album/movie/show title
 /// TCOM Composer
-/// TCON Content type, better known as a genre. Its recommended to write full strings to this frame instead of ID3v1 genres.
+/// TCON Content type, better known as a genre. Its recommended to write full strings to this frame instead of ID3v1 genres. Using [`Genre`](Genre) here is recommended to decode ID3v1 genres.
 /// TCOP Copyright message or notice for this audio. Must be prefixed with a year and a space.
 /// TENC Person/organization that encoded this audio file
 /// TEXT Lyricist(s)/Writer(s) for this audio
@@ -89,7 +90,8 @@ use std::fmt::{self, Display, Formatter};
 /// These frames contain numeric strings, or strings that should only contain the letters 0-9. Musikr does
 /// not enforce this restriction however, since other taggers will not follow this rule and put other information
 /// in anyway. It's recommended not to assume that all frames will be numeric when parsing, but to enforce the
-/// invariant when writing new frames.
+/// invariant when writing new frames. [`number`](TextFrame::number) and [`set_number`](TextFrame::set_number)
+/// can be used to do both.
 ///
 /// **Note:** When upgrading, musikr will only extract numeric information from these frames when upgrading.
 /// Malformed frames may result in lost information.
@@ -99,7 +101,7 @@ use std::fmt::{self, Display, Formatter};
 /// TDLY The delay between the end of this song and the next song in a playlist, in millis
 /// TLEN The length of this audio, in millis
 /// TYER [ID3v2.3] The year(s) this audio was recorded, formatted as YYYY. Must be at least 4 characters.
-/// TDAT [ID3v2.3] The date(s) this audio was recorded, formatted as MMDD. Must be 4 characters.
+/// TDAT [ID3v2.3] The date(s) this audio was recorded, formatted as DDMM. Must be 4 characters.
 /// TIME [ID3v2.3] The time(s) this audio was recorded, formatted as HHMM. Must be 4 characters.
 /// TORY [ID3v2.3] The year this audio was released, formatted as YYYY. Must be at least 4 characters.
 /// TSIZ [ID3v2.3] The size of the audio, in bytes.
@@ -109,7 +111,8 @@ use std::fmt::{self, Display, Formatter};
 /// #### Numeric Part Frames
 /// These are subset of numeric frames that are numeric strings `NN` that can be optionally
 /// extended with a "total" value, forming `NN/TT`. Like numeric frames, musikr does not enforce these
-/// invariants.
+/// invariants. [`part`](TextFrame::part) and [`set_part`](TextFrame::set_part) can be used to read and
+/// write these frames as a [`PartNumber`](PartNumber).
 ///
 /// ```text
 /// TPOS          The part of an set this track comes from, such as a collection of albums
@@ -121,7 +124,9 @@ use std::fmt::{self, Display, Formatter};
 ///
 /// These are frames that represent a timestamp, formatted as `YYYY-MM-DDTHH:MM:SS`. Precision can be
 /// tuned, meaning that `YYYY`, `YYYY-MM`, `YYYY-MM-DD`, `YYYY-MM-DDTHH`, `YYYY-MM-DDTHH:MM`,
-/// `YYYY-MM-DD-THH:MM:SS` are all valid timestamps.
+/// `YYYY-MM-DD-THH:MM:SS` are all valid timestamps. [`timestamp`](TextFrame::timestamp) and
+/// [`set_timestamp`](TextFrame::set_timestamp) can be used to read and write these frames as a
+/// [`Timestamp`](Timestamp).
 ///
 /// Its recommended to use these frames instead of the legacy TYER, TDAT, TIME, and TORY frames,
 /// as they will be automatically turned into those counterparts when saved.
@@ -192,6 +197,65 @@ impl TextFrame {
         )
     }
 
+    /// Returns the first string of this frame as an integer.
+    ///
+    /// This is meant for numeric frames. Since other taggers will put other information in
+    /// these frames, the first sequence of digits in the string is used, so `"120 BPM"` will
+    /// become `120`. `None` is returned if there are no digits or if the number is too large.
+    pub fn number(&self) -> Option<u64> {
+        digit_runs(self.text.first()?).next()?.parse().ok()
+    }
+
+    /// Replaces the text of this frame with `number`.
+    pub fn set_number(&mut self, number: u64) {
+        self.text = vec![number.to_string()];
+    }
+
+    /// Returns the first string of this frame as a [`PartNumber`](PartNumber).
+    ///
+    /// This is meant for numeric part frames. See [`PartNumber::parse`](PartNumber::parse)
+    /// for how the string is interpreted.
+    pub fn part(&self) -> Option<PartNumber> {
+        PartNumber::parse(self.text.first()?)
+    }
+
+    /// Replaces the text of this frame with `part`, formatted as `NN/TT`.
+    pub fn set_part(&mut self, part: PartNumber) {
+        self.text = vec![part.to_string()];
+    }
+
+    /// Returns the first string of this frame as a [`Timestamp`](Timestamp).
+    ///
+    /// This is meant for timestamp frames. `None` is returned if the string is not a valid
+    /// timestamp.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.text.first()?.parse().ok()
+    }
+
+    /// Replaces the text of this frame with `timestamp`.
+    pub fn set_timestamp(&mut self, timestamp: Timestamp) {
+        self.text = vec![timestamp.to_string()];
+    }
+
+    /// Returns every genre in this frame.
+    ///
+    /// This is meant for `TCON`. See [`Genre::parse`](Genre::parse) for how each string
+    /// is interpreted.
+    pub fn genres(&self) -> Vec<Genre> {
+        self.text
+            .iter()
+            .flat_map(|text| Genre::parse(text))
+            .collect()
+    }
+
+    /// Replaces the text of this frame with `genres`, written in their ID3v2.4 form.
+    ///
+    /// ID3v1 genres will be rewritten into their ID3v2.3 form if the tag is updated from
+    /// ID3v2.4 to ID3v2.3.
+    pub fn set_genres(&mut self, genres: &[Genre]) {
+        self.text = genres.iter().map(Genre::to_v4).collect();
+    }
+
     pub(crate) fn parse(frame_id: FrameId, stream: &mut BufStream) -> ParseResult<Self> {
         let encoding = encoding::parse(stream)?;
         let text = parse_text(encoding, stream);
@@ -235,6 +299,76 @@ impl Display for TextFrame {
     }
 }
 
+/// A position in a numbered set, such as a track number.
+///
+/// This is the value of numeric part frames like `TRCK`, `TPOS`, and `MVIN`, which are written
+/// as `NN` or `NN/TT`.
+///
+/// # Example
+/// ```
+/// use musikr::id3v2::frames::text::PartNumber;
+///
+/// let part = PartNumber::parse("03 of 12").unwrap();
+///
+/// assert_eq!(part, PartNumber::new(3, Some(12)));
+/// assert_eq!(part.to_string(), "3/12");
+/// assert_eq!(PartNumber::parse("Track 7"), Some(PartNumber::new(7, None)));
+/// assert_eq!(PartNumber::parse("Bonus"), None);
+/// ```
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy, Default, Hash)]
+pub struct PartNumber {
+    /// The position in the set.
+    pub number: u32,
+    /// The total amount of parts in the set, if known.
+    pub total: Option<u32>,
+}
+
+impl PartNumber {
+    /// Creates an instance.
+    pub fn new(number: u32, total: Option<u32>) -> Self {
+        Self { number, total }
+    }
+
+    /// Leniently parses a part number from `text`.
+    ///
+    /// The first sequence of digits in `text` is the number, and the second sequence of digits
+    /// is the total, if present. This allows non-standard strings such as `"03 of 12"` to
+    /// be read. `None` is returned if there are no digits or if a number is too large.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut runs = digit_runs(text);
+        let number = runs.next()?.parse().ok()?;
+
+        let total = match runs.next() {
+            Some(total) => Some(total.parse().ok()?),
+            None => None,
+        };
+
+        Some(Self { number, total })
+    }
+}
+
+impl Display for PartNumber {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.total {
+            Some(total) => write![f, "{}/{}", self.number, total],
+            None => write![f, "{}", self.number],
+        }
+    }
+}
+
+impl FromStr for PartNumber {
+    type Err = PartNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).ok_or(PartNumberError(()))
+    }
+}
+
+impl_newtype_err! {
+    /// The error returned when a [`PartNumber`](PartNumber) could not be parsed.
+    PartNumberError => "string did not contain a part number"
+}
+
 /// Text information not represented by other frames.
 ///
 /// This frame can be used to add program-defined tags without having to create a new frame
@@ -307,10 +441,11 @@ impl Display for UserTextFrame {
 
 /// A mapping between involved people and their roles.
 ///
-/// All entries in this frame are kept in alphabetical order, by role. A role cannot
-/// be mapped to multiple strings. It's recommended that `TIPL` and `TMCL` are used
-/// over `IPLS`, as those frames will automatically be downgraded to `IPLS` if the
-/// tag is saved with ID3v2.3.
+/// The entries are stored as [`Credits`](Credits), so a role can be credited to multiple
+/// people and the entries are written in the same order that they were read. It's recommended
+/// that `TIPL` and `TMCL` are used over `IPLS`, as those frames will automatically be downgraded
+/// to `IPLS` if the tag is saved with ID3v2.3. Since `IPLS` does not distinguish musicians from
+/// other roles, its entries are always upgraded into `TIPL`.
 ///
 /// ```text
 /// IPLS [ID3v2.3] Maps between a role and a list of people for that role
@@ -325,9 +460,8 @@ pub struct CreditsFrame {
     frame_id: FrameId,
     /// The encoding that the frame will use to write `people`.
     pub encoding: Encoding,
-    /// A mapping between roles and people. Multiple people can be delimited with
-    /// a comma or similar separator.
-    pub people: BTreeMap<String, String>,
+    /// The people credited in this frame, by role.
+    pub people: Credits,
 }
 
 impl Frame for CreditsFrame {
@@ -349,7 +483,7 @@ impl Frame for CreditsFrame {
     fn is_empty(&self) -> bool {
         self.people
             .iter()
-            .filter(|(people, role)| !role.is_empty() && !people.is_empty())
+            .filter(|(role, person)| !role.is_empty() && !person.is_empty())
             .count()
             == 0
     }
@@ -362,8 +496,8 @@ impl Frame for CreditsFrame {
 
         // To prevent lone pairs causing malformed frames, we filter out all
         // role-people pairs that are partially or completely empty.
-        let people = self.people.iter().filter(|(role, people)| {
-            if role.is_empty() || people.is_empty() {
+        let people = self.people.iter().filter(|(role, person)| {
+            if role.is_empty() || person.is_empty() {
                 warn!("dropping incomplete role-people pair in {}", self.frame_id);
                 false
             } else {
@@ -371,16 +505,14 @@ impl Frame for CreditsFrame {
             }
         });
 
-        // Rendering a CreditsFrame is similar to a TextFrame, but has to be done
-        // in pairs since there seems to be no way to zip keys and values into
-        // an iterator without having to bring in a dependency.
-        for (i, (role, people)) in people.enumerate() {
+        // Rendering a CreditsFrame is similar to a TextFrame, but has to be done in pairs.
+        for (i, (role, person)) in people.enumerate() {
             if i > 0 {
                 result.resize(result.len() + encoding.nul_size(), 0);
             }
 
             result.extend(string::render_terminated(encoding, role));
-            result.extend(string::render(encoding, people));
+            result.extend(string::render(encoding, person));
         }
 
         result
@@ -404,7 +536,7 @@ impl CreditsFrame {
         Self {
             frame_id,
             encoding: Encoding::default(),
-            people: BTreeMap::new(),
+            people: Credits::new(),
         }
     }
 
@@ -419,7 +551,7 @@ impl CreditsFrame {
         let encoding = encoding::parse(stream)?;
         let mut text = parse_text(encoding, stream);
 
-        if text.len() % 2 != 0 {
+        if !text.len().is_multiple_of(2) {
             // The spec says that IPLS/TIPL/TMCL must contain an even number of entries.
             // If this frame does have an incomplete pair, we just pop it off and move on.
             info!(
@@ -430,16 +562,16 @@ impl CreditsFrame {
             text.pop();
         }
 
-        // Collect the parsed text into a single people map by role -> person.
-        let mut people = BTreeMap::new();
+        // Collect the parsed text into role -> person pairs, keeping any duplicate roles.
+        let mut people = Credits::new();
         let mut text = text.into_iter();
 
         while let Some(role) = text.next() {
             // We eliminated the possibility of an incomplete pair earlier, so we can
             // just unwrap here
-            let role_people = text.next().unwrap();
+            let person = text.next().unwrap();
 
-            people.insert(role, role_people);
+            people.add(role, person);
         }
 
         Ok(Self {
@@ -456,11 +588,11 @@ impl CreditsFrame {
 
 impl Display for CreditsFrame {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        for (i, (role, people)) in self.people.iter().enumerate() {
+        for (i, (role, person)) in self.people.iter().enumerate() {
             if i < self.people.len() - 1 {
-                writeln![f, "{}: {}", role, people]?;
+                writeln![f, "{}: {}", role, person]?;
             } else {
-                write![f, "{}: {}", role, people]?;
+                write![f, "{}: {}", role, person]?;
             }
         }
 
@@ -468,6 +600,206 @@ impl Display for CreditsFrame {
     }
 }
 
+/// The people credited in a [`CreditsFrame`](CreditsFrame), by role.
+///
+/// This is a multimap, so a role can be credited to any amount of people. Entries are kept in
+/// the order they were added, including duplicates, so that a frame is written exactly as
+/// it was read. Lookups compare roles with [`normalize_role`](normalize_role), so looking
+/// up `"mix"` will also find people credited as `"Mixer"` or `"Mixed by"`.
+///
+/// # Example
+/// ```
+/// use musikr::id3v2::frames::text::Credits;
+///
+/// let mut credits = Credits::new();
+/// credits.add(String::from("Producer"), String::from("Sarah Oliver"));
+/// credits.add(String::from("Mixed by"), String::from("Matt Carver"));
+/// credits.add(String::from("producer"), String::from("John Smith"));
+///
+/// assert_eq!(credits.get("producer"), ["Sarah Oliver", "John Smith"]);
+/// assert_eq!(credits["mix"], "Matt Carver");
+/// assert_eq!(credits.len(), 3);
+/// ```
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct Credits(Vec<(String, String)>);
+
+impl Credits {
+    /// Creates an empty instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Credits `person` for `role`, after anyone already credited.
+    pub fn add(&mut self, role: String, person: String) {
+        self.0.push((role, person))
+    }
+
+    /// Returns everyone credited for `role`, in order.
+    pub fn get(&self, role: &str) -> Vec<&str> {
+        self.matching(role)
+            .map(|(_, person)| person.as_str())
+            .collect()
+    }
+
+    /// Returns if anyone is credited for `role`.
+    pub fn contains_role(&self, role: &str) -> bool {
+        !self.get(role).is_empty()
+    }
+
+    /// Returns every role as it was written, in order and without duplicates.
+    pub fn roles(&self) -> Vec<&str> {
+        let mut roles: Vec<&str> = Vec::new();
+
+        for (role, _) in self.iter() {
+            if !roles.contains(&role) {
+                roles.push(role)
+            }
+        }
+
+        roles
+    }
+
+    /// Removes everyone credited for `role`, returning them in order.
+    pub fn remove(&mut self, role: &str) -> Vec<String> {
+        let role = normalize_role(role);
+        let (removed, kept) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition(|(other, _)| normalize_role(other) == role);
+
+        self.0 = kept;
+
+        removed.into_iter().map(|(_, person)| person).collect()
+    }
+
+    /// Retains only the entries specified by the predicate.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&str, &str) -> bool,
+    {
+        self.0.retain(|(role, person)| keep(role, person))
+    }
+
+    /// Returns an iterator over every role and person, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.0
+            .iter()
+            .map(|(role, person)| (role.as_str(), person.as_str()))
+    }
+
+    /// Returns a mutable iterator over every role and person, in order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&mut String, &mut String)> + '_ {
+        self.0.iter_mut().map(|(role, person)| (role, person))
+    }
+
+    /// Returns the amount of entries.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn matching<'a>(&'a self, role: &str) -> impl Iterator<Item = &'a (String, String)> + 'a {
+        let role = normalize_role(role);
+        self.0
+            .iter()
+            .filter(move |(other, _)| normalize_role(other) == role)
+    }
+}
+
+impl Index<&str> for Credits {
+    type Output = String;
+
+    /// Returns the first person credited for `role`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if no one is credited for `role`.
+    fn index(&self, role: &str) -> &Self::Output {
+        match self.matching(role).next() {
+            Some((_, person)) => person,
+            None => panic!("no one is credited for {}", role),
+        }
+    }
+}
+
+impl Extend<(String, String)> for Credits {
+    fn extend<I: IntoIterator<Item = (String, String)>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl FromIterator<(String, String)> for Credits {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Credits {
+    type Item = (String, String);
+    type IntoIter = vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// The common spellings of roles, by the role they are normalized to.
+static ROLES: &[(&str, &[&str])] = &[
+    // The roles that ID3v2.4 defines for TIPL
+    ("arranger", &["arranged", "arrangement", "arranging"]),
+    ("engineer", &["engineered", "engineering"]),
+    (
+        "DJ-mix",
+        &["dj mix", "dj-mixed", "dj mixed", "dj-mixer", "dj mixer"],
+    ),
+    ("mix", &["mixer", "mixed", "mixing"]),
+    ("producer", &["produced", "production"]),
+    // Common instruments in TMCL
+    ("bass", &["bassist", "bass guitar", "bass guitarist"]),
+    ("guitar", &["guitarist", "guitars"]),
+    ("drums", &["drummer", "drum"]),
+    ("vocals", &["vocal", "vocalist", "voice", "singer"]),
+    ("piano", &["pianist"]),
+    ("keyboards", &["keyboard", "keyboardist", "keys"]),
+    ("violin", &["violinist"]),
+    ("cello", &["cellist"]),
+    ("saxophone", &["saxophonist", "sax"]),
+    ("trumpet", &["trumpeter"]),
+    ("percussion", &["percussionist"]),
+];
+
+/// Normalizes a role so that different spellings of it can be compared.
+///
+/// Roles are lowercased, have their whitespace collapsed, and have a trailing `by` removed.
+/// Common spellings of the `TIPL` roles defined by ID3v2.4 and of common instruments are
+/// then replaced with a single name, so `"Mixed by"` and `"mixer"` both become `"mix"`.
+///
+/// # Example
+/// ```
+/// use musikr::id3v2::frames::text::normalize_role;
+///
+/// assert_eq!(normalize_role("Mixed by"), "mix");
+/// assert_eq!(normalize_role("DJ Mixer"), "DJ-mix");
+/// assert_eq!(normalize_role("Bassist"), "bass");
+/// assert_eq!(normalize_role("  Lead   Vocals "), "lead vocals");
+/// ```
+pub fn normalize_role(role: &str) -> String {
+    let role = role.split_whitespace().collect::<Vec<_>>().join(" ");
+    let role = role.to_lowercase();
+    let role = role.strip_suffix(" by").unwrap_or(&role);
+
+    for (name, spellings) in ROLES {
+        if name.eq_ignore_ascii_case(role) || spellings.contains(&role) {
+            return String::from(*name);
+        }
+    }
+
+    String::from(role)
+}
+
 /// A frame that contains a comment.
 ///
 /// This frame differs from [`UserTextFrame`](UserTextFrame) in that instead of containing
@@ -651,6 +983,417 @@ impl_newtype_err! {
     LanguageError => "language was not a 3-byte sequence of ascii alphabetic chars"
 }
 
+/// A date and time, as stored in ID3v2.4 timestamp frames.
+///
+/// Timestamps are written as `yyyy-MM-ddTHH:mm:ss`, but can have a reduced precision by leaving
+/// out any amount of fields from the end, down to just `yyyy`. All fields are validated, so a
+/// timestamp will always represent a real date and time. Timestamps are ordered chronologically,
+/// with less precise timestamps ordered before more precise timestamps that they contain.
+///
+/// ID3v2.3 does not have timestamp frames, and instead splits the year, date and time
+/// into the `TYER`, `TDAT` and `TIME` frames. [`from_v3`](Timestamp::from_v3) and
+/// [`to_v3`](Timestamp::to_v3) convert between the two.
+///
+/// # Example
+/// ```
+/// use musikr::id3v2::frames::text::Timestamp;
+///
+/// let stamp: Timestamp = "2020-10-10T12:34".parse().unwrap();
+///
+/// assert_eq!(stamp.year(), 2020);
+/// assert_eq!(stamp.minute(), Some(34));
+/// assert_eq!(stamp.second(), None);
+/// assert_eq!(stamp.to_v3(), (String::from("2020"), Some(String::from("1010")), Some(String::from("1234"))));
+/// assert!("2020-02-30".parse::<Timestamp>().is_err());
+/// ```
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy, Hash)]
+pub struct Timestamp {
+    year: u16,
+    // The month, day, hour, minute, and second, in that order. A field can only be
+    // present if all the fields before it are.
+    fields: [Option<u8>; 5],
+}
+
+impl Timestamp {
+    /// Fallibly creates an instance.
+    ///
+    /// # Errors
+    /// If a field is out of range, or if a field is present when a field before it is
+    /// not, then an error will be returned.
+    pub fn try_new(
+        year: u16,
+        month: Option<u8>,
+        day: Option<u8>,
+        hour: Option<u8>,
+        minute: Option<u8>,
+        second: Option<u8>,
+    ) -> Result<Self, TimestampError> {
+        let stamp = Self {
+            year,
+            fields: [month, day, hour, minute, second],
+        };
+
+        if stamp.is_valid() {
+            Ok(stamp)
+        } else {
+            Err(TimestampError(()))
+        }
+    }
+
+    /// Creates a timestamp from the strings of the ID3v2.3 `TYER`, `TDAT`, and `TIME` frames.
+    ///
+    /// `year` is formatted as `yyyy`, `date` as `DDMM`, and `time` as `HHMM`. Years shorter
+    /// than four digits are tolerated.
+    ///
+    /// # Errors
+    /// If any of the strings are malformed, or if `time` is present without `date`, then an
+    /// error will be returned.
+    pub fn from_v3(
+        year: &str,
+        date: Option<&str>,
+        time: Option<&str>,
+    ) -> Result<Self, TimestampError> {
+        let year = year.trim();
+
+        if year.is_empty() || year.len() > 4 {
+            return Err(TimestampError(()));
+        }
+
+        let year = parse_digits(year.as_bytes()).ok_or(TimestampError(()))? as u16;
+
+        // TDAT and TIME are both two pairs of digits.
+        let pairs = |text: Option<&str>| match text.map(|text| text.trim().as_bytes()) {
+            Some([a, b, c, d]) => match (parse_digits(&[*a, *b]), parse_digits(&[*c, *d])) {
+                (Some(first), Some(second)) => Ok((Some(first as u8), Some(second as u8))),
+                _ => Err(TimestampError(())),
+            },
+            Some(_) => Err(TimestampError(())),
+            None => Ok((None, None)),
+        };
+
+        let (day, month) = pairs(date)?;
+        let (hour, minute) = pairs(time)?;
+
+        Self::try_new(year, month, day, hour, minute, None)
+    }
+
+    /// Returns the year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the month, from 1 to 12.
+    pub fn month(&self) -> Option<u8> {
+        self.fields[0]
+    }
+
+    /// Returns the day of the month, starting from 1.
+    pub fn day(&self) -> Option<u8> {
+        self.fields[1]
+    }
+
+    /// Returns the hour, from 0 to 23.
+    pub fn hour(&self) -> Option<u8> {
+        self.fields[2]
+    }
+
+    /// Returns the minute, from 0 to 59.
+    pub fn minute(&self) -> Option<u8> {
+        self.fields[3]
+    }
+
+    /// Returns the second, from 0 to 59.
+    pub fn second(&self) -> Option<u8> {
+        self.fields[4]
+    }
+
+    /// Splits this timestamp into the strings of the ID3v2.3 `TYER`, `TDAT`, and `TIME` frames.
+    ///
+    /// The date is only returned if the timestamp has a day, and the time is only returned
+    /// if it has a minute. Seconds cannot be represented and are discarded.
+    pub fn to_v3(&self) -> (String, Option<String>, Option<String>) {
+        let pair =
+            |first: Option<u8>, second: Option<u8>| Some(format!["{:02}{:02}", first?, second?]);
+
+        (
+            format!["{:04}", self.year],
+            pair(self.day(), self.month()),
+            pair(self.hour(), self.minute()),
+        )
+    }
+
+    /// Parses as many fields of `text` as form a valid timestamp. The timestamp is returned
+    /// alongside whether all of `text` was used.
+    pub(crate) fn parse_prefix(text: &str) -> Option<(Self, bool)> {
+        let text = text.trim().as_bytes();
+
+        let mut stamp = Self {
+            year: parse_digits(text.get(0..4)?)? as u16,
+            fields: [None; 5],
+        };
+
+        let mut pos = 4;
+
+        // Every field after the year is a separator followed by two digits.
+        for (i, sep) in [b'-', b'-', b'T', b':', b':'].into_iter().enumerate() {
+            let value = match text.get(pos..pos + 3) {
+                Some([ch, a, b]) if *ch == sep => parse_digits(&[*a, *b]),
+                _ => None,
+            };
+
+            let mut next = stamp;
+            next.fields[i] = value.map(|value| value as u8);
+
+            if value.is_none() || !next.is_valid() {
+                break;
+            }
+
+            stamp = next;
+            pos += 3;
+        }
+
+        Some((stamp, pos == text.len()))
+    }
+
+    fn is_valid(&self) -> bool {
+        let [month, day, hour, minute, second] = self.fields;
+
+        let leap = self.year.is_multiple_of(4)
+            && (!self.year.is_multiple_of(100) || self.year.is_multiple_of(400));
+
+        let days = match month {
+            Some(2) if leap => 29,
+            Some(2) => 28,
+            Some(4 | 6 | 9 | 11) => 30,
+            _ => 31,
+        };
+
+        self.fields
+            .windows(2)
+            .all(|pair| pair[0].is_some() || pair[1].is_none())
+            && self.year <= 9999
+            && month.is_none_or(|month| (1..=12).contains(&month))
+            && day.is_none_or(|day| (1..=days).contains(&day))
+            && hour.is_none_or(|hour| hour < 24)
+            && minute.is_none_or(|minute| minute < 60)
+            && second.is_none_or(|second| second < 60)
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write![f, "{:04}", self.year]?;
+
+        for (sep, field) in ['-', '-', 'T', ':', ':'].iter().zip(self.fields) {
+            match field {
+                Some(field) => write![f, "{}{:02}", sep, field]?,
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = TimestampError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::parse_prefix(s) {
+            Some((stamp, true)) => Ok(stamp),
+            _ => Err(TimestampError(())),
+        }
+    }
+}
+
+impl_newtype_err! {
+    /// The error returned when a [`Timestamp`](Timestamp) is not valid.
+    TimestampError => "timestamp was not a valid yyyy-MM-ddTHH:mm:ss date"
+}
+
+/// The genres defined by ID3v1, including the extensions added by Winamp.
+///
+/// The first 80 genres are from the ID3v1 specification, while the rest were added by Winamp
+/// and are now widely supported. A [`Genre::Id3v1`](Genre::Id3v1) genre refers to an index
+/// in this table.
+#[rustfmt::skip]
+pub static GENRES: [&str; 192] = [
+    // ID3v1
+    "Blues", "Classic Rock", "Country", "Dance", "Disco", "Funk", "Grunge", "Hip-Hop", "Jazz",
+    "Metal", "New Age", "Oldies", "Other", "Pop", "R&B", "Rap", "Reggae", "Rock", "Techno",
+    "Industrial", "Alternative", "Ska", "Death Metal", "Pranks", "Soundtrack", "Euro-Techno",
+    "Ambient", "Trip-Hop", "Vocal", "Jazz+Funk", "Fusion", "Trance", "Classical", "Instrumental",
+    "Acid", "House", "Game", "Sound Clip", "Gospel", "Noise", "Alternative Rock", "Bass", "Soul",
+    "Punk", "Space", "Meditative", "Instrumental Pop", "Instrumental Rock", "Ethnic", "Gothic",
+    "Darkwave", "Techno-Industrial", "Electronic", "Pop-Folk", "Eurodance", "Dream",
+    "Southern Rock", "Comedy", "Cult", "Gangsta", "Top 40", "Christian Rap", "Pop/Funk", "Jungle",
+    "Native American", "Cabaret", "New Wave", "Psychedelic", "Rave", "Showtunes", "Trailer",
+    "Lo-Fi", "Tribal", "Acid Punk", "Acid Jazz", "Polka", "Retro", "Musical", "Rock & Roll",
+    "Hard Rock",
+    // Winamp extensions
+    "Folk", "Folk-Rock", "National Folk", "Swing", "Fast Fusion", "Bebop", "Latin", "Revival",
+    "Celtic", "Bluegrass", "Avantgarde", "Gothic Rock", "Progressive Rock", "Psychedelic Rock",
+    "Symphonic Rock", "Slow Rock", "Big Band", "Chorus", "Easy Listening", "Acoustic", "Humour",
+    "Speech", "Chanson", "Opera", "Chamber Music", "Sonata", "Symphony", "Booty Bass", "Primus",
+    "Porn Groove", "Satire", "Slow Jam", "Club", "Tango", "Samba", "Folklore", "Ballad",
+    "Power Ballad", "Rhythmic Soul", "Freestyle", "Duet", "Punk Rock", "Drum Solo", "A Cappella",
+    "Euro-House", "Dance Hall", "Goa", "Drum & Bass", "Club-House", "Hardcore", "Terror", "Indie",
+    "BritPop", "Afro-Punk", "Polsk Punk", "Beat", "Christian Gangsta Rap", "Heavy Metal",
+    "Black Metal", "Crossover", "Contemporary Christian", "Christian Rock", "Merengue", "Salsa",
+    "Thrash Metal", "Anime", "JPop", "Synthpop", "Abstract", "Art Rock", "Baroque", "Bhangra",
+    "Big Beat", "Breakbeat", "Chillout", "Downtempo", "Dub", "EBM", "Eclectic", "Electro",
+    "Electroclash", "Emo", "Experimental", "Garage", "Global", "IDM", "Illbient", "Industro-Goth",
+    "Jam Band", "Krautrock", "Leftfield", "Lounge", "Math Rock", "New Romantic", "Nu-Breakz",
+    "Post-Punk", "Post-Rock", "Psytrance", "Shoegaze", "Space Rock", "Trop Rock", "World Music",
+    "Neoclassical", "Audiobook", "Audio Theatre", "Neue Deutsche Welle", "Podcast", "Indie Rock",
+    "G-Funk", "Dubstep", "Garage Rock", "Psybient",
+];
+
+/// A genre, as stored in the `TCON` frame.
+///
+/// ID3v2.3 refers to ID3v1 genres with parenthesized numbers like `(17)`, optionally followed
+/// by a refinement like `(17)Hard Rock`. ID3v2.4 instead writes each genre as a separate string,
+/// with ID3v1 genres written as plain numbers like `17`. Both versions also define `RX` for
+/// remixes and `CR` for covers. [`parse`](Genre::parse) understands either form, while
+/// [`to_v3`](Genre::to_v3) and [`to_v4`](Genre::to_v4) write them.
+///
+/// # Example
+/// ```
+/// use musikr::id3v2::frames::text::Genre;
+///
+/// let genres = Genre::parse("(17)(RX)Hard Rock");
+///
+/// assert_eq!(genres, [Genre::Id3v1(17), Genre::Remix, Genre::Name(String::from("Hard Rock"))]);
+/// assert_eq!(genres[0].to_string(), "Rock");
+/// assert_eq!(genres[0].to_v4(), "17");
+/// assert_eq!(Genre::parse("17"), [Genre::Id3v1(17)]);
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub enum Genre {
+    /// A genre from [`GENRES`](GENRES), referred to by its index.
+    Id3v1(u8),
+    /// A remix of another work, written as `RX`.
+    Remix,
+    /// A cover of another work, written as `CR`.
+    Cover,
+    /// Any other genre.
+    Name(String),
+}
+
+impl Genre {
+    /// Parses a string from a `TCON` frame into the genres it contains.
+    ///
+    /// Any amount of ID3v2.3 references are read from the start of the string, with the rest
+    /// of the string being a genre of its own. This refinement is discarded if it is just the
+    /// name of a referenced genre, as in `(17)Rock`. A string without any references, such as
+    /// an ID3v2.4 value, is read as an ID3v1 number, `RX`, `CR`, or a name.
+    pub fn parse(text: &str) -> Vec<Self> {
+        let mut genres = Vec::new();
+        let mut rest = text;
+
+        // A refinement that starts with a parenthesis is escaped as "((".
+        while !rest.starts_with("((") {
+            let reference = rest
+                .strip_prefix('(')
+                .and_then(|inner| Some((inner, inner.find(')')?)))
+                .and_then(|(inner, end)| Some((Self::parse_reference(&inner[..end])?, end)));
+
+            match reference {
+                Some((genre, end)) => {
+                    genres.push(genre);
+                    rest = &rest[end + 2..];
+                }
+                None => break,
+            }
+        }
+
+        if rest.starts_with("((") {
+            rest = &rest[1..];
+        }
+
+        let rest = rest.trim();
+
+        if rest.is_empty() {
+            return genres;
+        }
+
+        if genres.is_empty() {
+            genres.push(Self::parse_reference(rest).unwrap_or_else(|| Self::Name(rest.into())));
+        } else if !genres
+            .iter()
+            .any(|genre| genre.to_string().eq_ignore_ascii_case(rest))
+        {
+            genres.push(Self::Name(rest.into()));
+        }
+
+        genres
+    }
+
+    /// Writes this genre in its ID3v2.3 form, such as `(17)`.
+    pub fn to_v3(&self) -> String {
+        match self {
+            Self::Id3v1(index) => format!["({})", index],
+            Self::Remix => String::from("(RX)"),
+            Self::Cover => String::from("(CR)"),
+            Self::Name(name) if name.starts_with('(') => format!["({}", name],
+            Self::Name(name) => name.clone(),
+        }
+    }
+
+    /// Writes this genre in its ID3v2.4 form, such as `17`.
+    pub fn to_v4(&self) -> String {
+        match self {
+            Self::Id3v1(index) => index.to_string(),
+            Self::Remix => String::from("RX"),
+            Self::Cover => String::from("CR"),
+            Self::Name(name) => name.clone(),
+        }
+    }
+
+    fn parse_reference(reference: &str) -> Option<Self> {
+        match reference {
+            "RX" => Some(Self::Remix),
+            "CR" => Some(Self::Cover),
+            _ => match parse_digits(reference.as_bytes()) {
+                Some(index) if !reference.is_empty() && (index as usize) < GENRES.len() => {
+                    Some(Self::Id3v1(index as u8))
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Display for Genre {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Id3v1(index) => match GENRES.get(*index as usize) {
+                Some(name) => write![f, "{}", name],
+                None => write![f, "{}", index],
+            },
+            Self::Remix => write![f, "Remix"],
+            Self::Cover => write![f, "Cover"],
+            Self::Name(name) => write![f, "{}", name],
+        }
+    }
+}
+
+/// Parses `digits` as a number, if it only contains ASCII digits.
+fn parse_digits(digits: &[u8]) -> Option<u32> {
+    digits.iter().try_fold(0u32, |acc, byte| {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+
+        acc.checked_mul(10)?.checked_add((byte - b'0') as u32)
+    })
+}
+
+/// Returns each sequence of ASCII digits in `text`.
+fn digit_runs(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|ch: char| !ch.is_ascii_digit())
+        .filter(|run| !run.is_empty())
+}
 
 fn fmt_text<D: Display>(f: &mut Formatter, text: &[D]) -> fmt::Result {
     for (i, string) in text.iter().enumerate() {
@@ -755,6 +1498,210 @@ mod tests {
         assert_eq!(frame.text[0], "The Fall of Math");
     }
 
+    #[test]
+    fn parse_part_numbers() {
+        let part = |number, total| Some(PartNumber::new(number, total));
+
+        assert_eq!(PartNumber::parse("3"), part(3, None));
+        assert_eq!(PartNumber::parse("03/12"), part(3, Some(12)));
+        assert_eq!(PartNumber::parse(" 3 / 12 "), part(3, Some(12)));
+        assert_eq!(PartNumber::parse("03 of 12"), part(3, Some(12)));
+        assert_eq!(PartNumber::parse("Disc 2"), part(2, None));
+        assert_eq!(PartNumber::parse("4/"), part(4, None));
+        assert_eq!(PartNumber::parse(""), None);
+        assert_eq!(PartNumber::parse("A-Side"), None);
+        assert_eq!(PartNumber::parse("99999999999"), None);
+        assert!("/".parse::<PartNumber>().is_err());
+
+        assert_eq!(PartNumber::new(3, None).to_string(), "3");
+        assert_eq!(PartNumber::new(3, Some(12)).to_string(), "3/12");
+    }
+
+    #[test]
+    fn parse_timestamps() {
+        let stamp = |text: &str| text.parse::<Timestamp>().unwrap();
+
+        for text in [
+            "2020",
+            "2020-10",
+            "2020-02-29",
+            "2020-10-10T12",
+            "2020-10-10T12:34",
+            "2020-10-10T12:34:56",
+        ] {
+            assert_eq!(stamp(text).to_string(), text);
+        }
+
+        for text in [
+            "",
+            "20",
+            "2020-1",
+            "2021-02-29",
+            "2020-13",
+            "2020-10-10 12:34",
+            "2020-10-10T24",
+            "2020-10-10T12:60",
+            "2020-10-10T12:34:56Z",
+        ] {
+            assert!(text.parse::<Timestamp>().is_err(), "{}", text);
+        }
+
+        assert_eq!(
+            Timestamp::parse_prefix("2020-10-10T40:40"),
+            Some((stamp("2020-10-10"), false))
+        );
+
+        let full = stamp("2020-10-10T12:34:56");
+        assert_eq!(full.year(), 2020);
+        assert_eq!(full.month(), Some(10));
+        assert_eq!(full.day(), Some(10));
+        assert_eq!(full.hour(), Some(12));
+        assert_eq!(full.minute(), Some(34));
+        assert_eq!(full.second(), Some(56));
+
+        assert!(Timestamp::try_new(2020, None, Some(1), None, None, None).is_err());
+        assert!(Timestamp::try_new(10000, None, None, None, None, None).is_err());
+    }
+
+    #[test]
+    fn order_timestamps() {
+        let mut stamps: Vec<Timestamp> = ["2020-10-10", "2019-12-31T23:59", "2020", "2020-10"]
+            .iter()
+            .map(|text| text.parse().unwrap())
+            .collect();
+
+        stamps.sort();
+
+        let stamps: Vec<String> = stamps.iter().map(|stamp| stamp.to_string()).collect();
+        assert_eq!(
+            stamps,
+            ["2019-12-31T23:59", "2020", "2020-10", "2020-10-10"]
+        );
+    }
+
+    #[test]
+    fn convert_v3_timestamps() {
+        let stamp = Timestamp::from_v3("2020", Some("3112"), Some("2359")).unwrap();
+        assert_eq!(stamp.to_string(), "2020-12-31T23:59");
+
+        assert_eq!(
+            stamp.to_v3(),
+            (
+                String::from("2020"),
+                Some(String::from("3112")),
+                Some(String::from("2359"))
+            )
+        );
+
+        let stamp = Timestamp::from_v3(" 999", None, None).unwrap();
+        assert_eq!(stamp.to_string(), "0999");
+        assert_eq!(stamp.to_v3(), (String::from("0999"), None, None));
+
+        let stamp: Timestamp = "2020-12-31T23".parse().unwrap();
+        assert_eq!(
+            stamp.to_v3(),
+            (String::from("2020"), Some(String::from("3112")), None)
+        );
+
+        assert!(Timestamp::from_v3("", None, None).is_err());
+        assert!(Timestamp::from_v3("20201", None, None).is_err());
+        assert!(Timestamp::from_v3("2020", Some("1231"), None).is_err());
+        assert!(Timestamp::from_v3("2020", Some("311"), None).is_err());
+        assert!(Timestamp::from_v3("2020", Some("3112"), Some("12:00")).is_err());
+        assert!(Timestamp::from_v3("2020", None, Some("1200")).is_err());
+    }
+
+    #[test]
+    fn timestamp_text() {
+        let mut frame = crate::text_frame!(b"TDRC", ["2020-10-10", "2021"]);
+        assert_eq!(frame.timestamp(), "2020-10-10".parse().ok());
+
+        frame.set_timestamp("2022-01".parse().unwrap());
+        assert_eq!(frame.text, &["2022-01"]);
+
+        frame.text = vec![String::from("October 2020")];
+        assert_eq!(frame.timestamp(), None);
+    }
+
+    #[test]
+    fn parse_genres() {
+        let name = |name: &str| Genre::Name(String::from(name));
+
+        assert_eq!(GENRES[17], "Rock");
+        assert_eq!(GENRES[79], "Hard Rock");
+        assert_eq!(GENRES[80], "Folk");
+        assert_eq!(GENRES[191], "Psybient");
+
+        assert_eq!(Genre::parse("(17)"), [Genre::Id3v1(17)]);
+        assert_eq!(Genre::parse("(17)Rock"), [Genre::Id3v1(17)]);
+        assert_eq!(Genre::parse("(17)(6)"), [Genre::Id3v1(17), Genre::Id3v1(6)]);
+        assert_eq!(
+            Genre::parse("(4)Eurodisco"),
+            [Genre::Id3v1(4), name("Eurodisco")]
+        );
+        assert_eq!(Genre::parse("(RX)(CR)"), [Genre::Remix, Genre::Cover]);
+        assert_eq!(Genre::parse("((Bonus) Jazz"), [name("(Bonus) Jazz")]);
+        assert_eq!(
+            Genre::parse("(17)((Bonus)"),
+            [Genre::Id3v1(17), name("(Bonus)")]
+        );
+        assert_eq!(Genre::parse("17"), [Genre::Id3v1(17)]);
+        assert_eq!(Genre::parse("RX"), [Genre::Remix]);
+        assert_eq!(Genre::parse("Post-Rock"), [name("Post-Rock")]);
+        assert_eq!(Genre::parse("(255)"), [name("(255)")]);
+        assert_eq!(Genre::parse("99999999999"), [name("99999999999")]);
+        assert_eq!(Genre::parse(""), []);
+
+        assert_eq!(Genre::Id3v1(17).to_string(), "Rock");
+        assert_eq!(Genre::Id3v1(255).to_string(), "255");
+        assert_eq!(Genre::Cover.to_string(), "Cover");
+    }
+
+    #[test]
+    fn render_genres() {
+        let genres = [
+            Genre::Id3v1(17),
+            Genre::Remix,
+            Genre::Cover,
+            Genre::Name(String::from("Post-Rock")),
+            Genre::Name(String::from("(Bonus)")),
+        ];
+
+        let v3: Vec<String> = genres.iter().map(Genre::to_v3).collect();
+        let v4: Vec<String> = genres.iter().map(Genre::to_v4).collect();
+
+        assert_eq!(v3, ["(17)", "(RX)", "(CR)", "Post-Rock", "((Bonus)"]);
+        assert_eq!(v4, ["17", "RX", "CR", "Post-Rock", "(Bonus)"]);
+
+        for text in v3 {
+            assert_eq!(Genre::parse(&text).len(), 1);
+        }
+
+        let mut frame = crate::text_frame!(b"TCON", ["(17)(6)", "Post-Rock"]);
+        assert_eq!(frame.genres()[0..2], [Genre::Id3v1(17), Genre::Id3v1(6)]);
+
+        frame.set_genres(&genres[0..2]);
+        assert_eq!(frame.text, &["17", "RX"]);
+    }
+
+    #[test]
+    fn numeric_text() {
+        let mut frame = crate::text_frame!(b"TBPM", ["120.5 BPM"]);
+        assert_eq!(frame.number(), Some(120));
+
+        frame.set_number(128);
+        assert_eq!(frame.text, &["128"]);
+
+        frame.text = vec![String::from("Fast")];
+        assert_eq!(frame.number(), None);
+
+        let mut frame = crate::text_frame!(b"TRCK", ["1 of 10", "2 of 10"]);
+        assert_eq!(frame.part(), Some(PartNumber::new(1, Some(10))));
+
+        frame.set_part(PartNumber::new(2, None));
+        assert_eq!(frame.text, &["2"]);
+    }
+
     #[test]
     fn render_text() {
         let frame = crate::text_frame! {
@@ -806,6 +1753,44 @@ mod tests {
         assert_render!(frame, TMCL_DATA);
     }
 
+    #[test]
+    fn credits_roles() {
+        let mut credits: Credits = [
+            ("Producer", "Sarah Oliver"),
+            ("Mixed by", "Matt Carver"),
+            ("Bass Guitar", "John Smith"),
+            ("producer", "Vanessa Evans"),
+        ]
+        .iter()
+        .map(|(role, person)| (String::from(*role), String::from(*person)))
+        .collect();
+
+        assert_eq!(credits.get("Producer"), ["Sarah Oliver", "Vanessa Evans"]);
+        assert_eq!(credits.get("mixer"), ["Matt Carver"]);
+        assert_eq!(credits.get("Bassist"), ["John Smith"]);
+        assert_eq!(credits["bass"], "John Smith");
+        assert!(!credits.contains_role("Engineer"));
+        assert_eq!(
+            credits.roles(),
+            ["Producer", "Mixed by", "Bass Guitar", "producer"]
+        );
+
+        assert_eq!(
+            credits.remove("produced by"),
+            ["Sarah Oliver", "Vanessa Evans"]
+        );
+        assert_eq!(credits.len(), 2);
+
+        credits.retain(|role, _| role != "Mixed by");
+
+        let entries: Vec<(&str, &str)> = credits.iter().collect();
+        assert_eq!(entries, [("Bass Guitar", "John Smith")]);
+
+        assert_eq!(normalize_role("Engineered By"), "engineer");
+        assert_eq!(normalize_role("dj-mix"), "DJ-mix");
+        assert_eq!(normalize_role("Theremin"), "theremin");
+    }
+
     #[test]
     fn valid_credits_ids() {
         let ids = [
@@ -102,7 +102,7 @@ macro_rules! credits_frame {
     ($id:expr, $($role:expr => $people:expr),+ $(,)?) => {
         {
             let mut frame = $crate::id3v2::frames::CreditsFrame::new($crate::id3v2::frames::FrameId::new($id));
-            $(frame.people.insert(String::from($role), String::from($people));)*
+            $(frame.people.add(String::from($role), String::from($people));)*
             frame
         }
     };
@@ -110,7 +110,7 @@ macro_rules! credits_frame {
         {
             let mut frame = $crate::id3v2::frames::CreditsFrame::new($crate::id3v2::frames::FrameId::new($id));
             frame.encoding = $enc;
-            $(frame.people.insert(String::from($role), String::from($people));)*
+            $(frame.people.add(String::from($role), String::from($people));)*
             frame
         }
     }
@@ -171,7 +171,7 @@ macro_rules! make_frame {
         )
         .unwrap();
 
-        let frame = if let crate::id3v2::frames::ParsedFrame::Frame(frame) = parsed {
+        let frame = if let crate::id3v2::frames::ParsedFrame::Frame(frame, _) = parsed {
             frame
         } else {
             panic!("cannot parse frame: {:?}", parsed)
@@ -188,7 +188,10 @@ macro_rules! assert_render {
         assert_eq!(
             crate::id3v2::frames::render(
                 &crate::id3v2::tag::TagHeader::with_version(crate::id3v2::tag::Version::V24),
-                &$frame
+                &$frame,
+                &crate::id3v2::frames::FrameFlags::default(),
+                &crate::id3v2::tag::CompressionPolicy::None,
+                None
             )
             .unwrap(),
             $data
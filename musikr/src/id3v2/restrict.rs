@@ -0,0 +1,263 @@
+use crate::core::string::Encoding;
+use crate::id3v2::frames::{
+    AttachedPictureFrame, ChapterFrame, CommentsFrame, CommercialFrame, CreditsFrame, Frame,
+    GeneralObjectFrame, OwnershipFrame, SyncedLyricsFrame, TableOfContentsFrame, TermsOfUseFrame,
+    TextFrame, UnsyncLyricsFrame, UserTextFrame, UserUrlFrame,
+};
+use crate::id3v2::tag::{
+    ImageEncodingRestriction, RestrictionChange, Restrictions, TextEncodingRestriction,
+};
+use crate::id3v2::FrameMap;
+use log::info;
+
+static ALLOWED_MIMES: &[&str] = &["image/png", "image/jpeg", "image/jpg"];
+
+/// Transforms the frames in `frames` so that they conform to the text and image
+/// `restrictions`, including the frames embedded in chapters. Tag size restrictions
+/// are handled when the tag is rendered.
+pub fn apply(frames: &mut FrameMap, restrictions: &Restrictions) -> Vec<RestrictionChange> {
+    let mut changes = Vec::new();
+
+    // The keys of a frame may change as we truncate text, so we have to remove and
+    // re-add every frame.
+    let keys: Vec<String> = frames.keys().cloned().collect();
+
+    for key in keys {
+        let flags = frames.flags(&key).unwrap_or_default();
+        let mut frame = frames.remove(&key).unwrap();
+
+        if restrictions.image_encoding == ImageEncodingRestriction::OnlyPngOrJpeg {
+            if let Some(apic) = frame.downcast::<AttachedPictureFrame>() {
+                let mime = apic.mime.to_lowercase();
+
+                if !ALLOWED_MIMES.contains(&mime.as_str()) {
+                    info!("removing disallowed picture {}", key);
+
+                    changes.push(RestrictionChange::RemovedPicture {
+                        key,
+                        mime: apic.mime.clone(),
+                    });
+
+                    continue;
+                }
+            }
+        }
+
+        if restrictions.text_encoding == TextEncodingRestriction::Latin1OrUtf8 {
+            restrict_encoding(frame.as_mut(), &key, &mut changes);
+        }
+
+        if let Some(max) = restrictions.text_size.max_chars() {
+            restrict_text(frame.as_mut(), &key, max, &mut changes);
+        }
+
+        if let Some(chap) = frame.downcast_mut::<ChapterFrame>() {
+            changes.extend(apply(&mut chap.frames, restrictions));
+        } else if let Some(ctoc) = frame.downcast_mut::<TableOfContentsFrame>() {
+            changes.extend(apply(&mut ctoc.frames, restrictions));
+        }
+
+        frames.add_with_flags(frame, flags);
+    }
+
+    changes
+}
+
+macro_rules! frame_encoding {
+    ($frame:expr, [$($ty:ty),+]) => {
+        $(if let Some(frame) = $frame.downcast_mut::<$ty>() {
+            Some(&mut frame.encoding)
+        } else)+ {
+            None
+        }
+    };
+}
+
+fn restrict_encoding(frame: &mut dyn Frame, key: &str, changes: &mut Vec<RestrictionChange>) {
+    let encoding = frame_encoding!(
+        frame,
+        [
+            TextFrame,
+            UserTextFrame,
+            CreditsFrame,
+            CommentsFrame,
+            UnsyncLyricsFrame,
+            SyncedLyricsFrame,
+            UserUrlFrame,
+            AttachedPictureFrame,
+            GeneralObjectFrame,
+            OwnershipFrame,
+            TermsOfUseFrame,
+            CommercialFrame
+        ]
+    );
+
+    if let Some(encoding) = encoding {
+        // UTF-8 can represent everything that UTF-16 can, so we use it over Latin1.
+        if !matches!(*encoding, Encoding::Latin1 | Encoding::Utf8) {
+            changes.push(RestrictionChange::Reencoded {
+                key: key.to_string(),
+                from: *encoding,
+                to: Encoding::Utf8,
+            });
+
+            *encoding = Encoding::Utf8;
+        }
+    }
+}
+
+fn restrict_text(
+    frame: &mut dyn Frame,
+    key: &str,
+    max: usize,
+    changes: &mut Vec<RestrictionChange>,
+) {
+    let mut truncate = |string: &mut String| {
+        if let Some((idx, _)) = string.char_indices().nth(max) {
+            string.truncate(idx);
+
+            changes.push(RestrictionChange::Truncated {
+                key: key.to_string(),
+                len: max,
+            })
+        }
+    };
+
+    if let Some(frame) = frame.downcast_mut::<TextFrame>() {
+        frame.text.iter_mut().for_each(truncate);
+    } else if let Some(frame) = frame.downcast_mut::<UserTextFrame>() {
+        truncate(&mut frame.desc);
+        frame.text.iter_mut().for_each(truncate);
+    } else if let Some(frame) = frame.downcast_mut::<CreditsFrame>() {
+        frame.people.iter_mut().for_each(|(role, person)| {
+            truncate(role);
+            truncate(person);
+        });
+    } else if let Some(frame) = frame.downcast_mut::<CommentsFrame>() {
+        truncate(&mut frame.desc);
+        truncate(&mut frame.text);
+    } else if let Some(frame) = frame.downcast_mut::<UnsyncLyricsFrame>() {
+        truncate(&mut frame.desc);
+        truncate(&mut frame.lyrics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id3v2::frames::FrameId;
+    use crate::id3v2::tag::{ImageSizeRestriction, TagSizeRestriction, TextSizeRestriction};
+
+    const RESTRICTIONS: Restrictions = Restrictions {
+        tag_size: TagSizeRestriction::Max32Frames4Kb,
+        text_encoding: TextEncodingRestriction::Latin1OrUtf8,
+        text_size: TextSizeRestriction::LessThan30Chars,
+        image_encoding: ImageEncodingRestriction::OnlyPngOrJpeg,
+        image_size: ImageSizeRestriction::None,
+    };
+
+    #[test]
+    fn restrict_frames() {
+        let mut frames = FrameMap::new();
+
+        let mut tit2 = TextFrame::new(FrameId::new(b"TIT2"));
+        tit2.encoding = Encoding::Utf16;
+        tit2.text = vec![String::from(
+            "A title that is far too long for an embedded player",
+        )];
+        frames.add(tit2);
+
+        let mut tpe1 = TextFrame::new(FrameId::new(b"TPE1"));
+        tpe1.encoding = Encoding::Latin1;
+        tpe1.text = vec![String::from("Artist")];
+        frames.add(tpe1);
+
+        frames.add(AttachedPictureFrame {
+            mime: String::from("image/bmp"),
+            desc: String::from("Bitmap"),
+            ..Default::default()
+        });
+
+        frames.add(AttachedPictureFrame {
+            mime: String::from("image/png"),
+            desc: String::from("Portable"),
+            ..Default::default()
+        });
+
+        let changes = apply(&mut frames, &RESTRICTIONS);
+
+        assert_eq!(
+            changes,
+            vec![
+                RestrictionChange::RemovedPicture {
+                    key: String::from("APIC:Bitmap"),
+                    mime: String::from("image/bmp")
+                },
+                RestrictionChange::Reencoded {
+                    key: String::from("TIT2"),
+                    from: Encoding::Utf16,
+                    to: Encoding::Utf8
+                },
+                RestrictionChange::Truncated {
+                    key: String::from("TIT2"),
+                    len: 30
+                }
+            ]
+        );
+
+        assert!(!frames.contains_key("APIC:Bitmap"));
+        assert!(frames.contains_key("APIC:Portable"));
+
+        let tit2 = frames["TIT2"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tit2.encoding, Encoding::Utf8);
+        assert_eq!(tit2.text[0], "A title that is far too long f");
+
+        let tpe1 = frames["TPE1"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tpe1.encoding, Encoding::Latin1);
+        assert_eq!(tpe1.text[0], "Artist");
+    }
+
+    #[test]
+    fn restrict_chapter_frames() {
+        let mut chap = ChapterFrame {
+            element_id: String::from("chp1"),
+            ..Default::default()
+        };
+
+        let mut tit2 = TextFrame::new(FrameId::new(b"TIT2"));
+        tit2.encoding = Encoding::Utf16Be;
+        tit2.text = vec![String::from("Chapter 1")];
+        chap.frames.add(tit2);
+
+        chap.frames.add(AttachedPictureFrame {
+            mime: String::from("image/gif"),
+            ..Default::default()
+        });
+
+        let mut frames = FrameMap::new();
+        frames.add(chap);
+
+        let changes = apply(&mut frames, &RESTRICTIONS);
+
+        assert_eq!(
+            changes,
+            vec![
+                RestrictionChange::RemovedPicture {
+                    key: String::from("APIC:"),
+                    mime: String::from("image/gif")
+                },
+                RestrictionChange::Reencoded {
+                    key: String::from("TIT2"),
+                    from: Encoding::Utf16Be,
+                    to: Encoding::Utf8
+                },
+            ]
+        );
+
+        let chap = frames["CHAP:chp1"].downcast::<ChapterFrame>().unwrap();
+        assert!(!chap.frames.contains_key("APIC:"));
+
+        let tit2 = chap.frames["TIT2"].downcast::<TextFrame>().unwrap();
+        assert_eq!(tit2.encoding, Encoding::Utf8);
+    }
+}
@@ -16,13 +16,13 @@ pub fn to_u28(raw: [u8; 4]) -> u32 {
     sum
 }
 
-/// Lossily converts a 35-byte syncsafe integer into a u32.
+/// Converts a 35-bit syncsafe integer into a u32.
 pub fn to_u35(mut raw: [u8; 5]) -> u32 {
     let mut sum: u32 = 0;
 
-    // Remove the last 5 bits of the first byte so that we don't overflow the u32.
+    // Remove the upper 3 bits of the first byte so that we don't overflow the u32.
     // The spec says that these bits shouldn't be used, so this is okay.
-    raw[0] &= 0x7;
+    raw[0] &= 0xF;
 
     for (i, &byte) in raw.iter().enumerate() {
         sum |= u32::from(byte) << ((4 - i) * 7);
@@ -79,9 +79,13 @@ pub fn decode(src: &mut BufStream) -> Vec<u8> {
     }
 
     // Since we have to look ahead, we'll sometimes need to add a lone u8 that wasnt able
-    // to be added initially.
+    // to be added initially. This can also be a sync guard after a trailing 0xFF.
     if src.remaining() == 1 {
-        dest.push(src.read_u8().unwrap());
+        let cur = src.read_u8().unwrap();
+
+        if !(last == 0xFF && cur == 0x00) {
+            dest.push(cur);
+        }
     }
 
     dest.shrink_to_fit();
@@ -89,11 +93,48 @@ pub fn decode(src: &mut BufStream) -> Vec<u8> {
     dest
 }
 
+/// Returns a `Vec<u8>` encoded with the ID3v2 synchronization scheme from `src`.
+///
+/// Any trailing `0xFF` byte will also be followed by a `0x00` byte, so that the encoded data
+/// cannot form a false sync with whatever comes after it.
+pub fn encode(src: &[u8]) -> Vec<u8> {
+    // Most data will have few to no false syncs, so reserving a little more space than src
+    // is usually enough to avoid any further allocations.
+    let mut dest = Vec::with_capacity(src.len() + src.len() / 64 + 1);
+
+    for (i, &cur) in src.iter().enumerate() {
+        dest.push(cur);
+
+        // This is the inverse of the two guards in decode. A lone 0xFF at the end of
+        // the data is also guarded.
+        if cur == 0xFF {
+            match src.get(i + 1) {
+                Some(&next) if next != 0x00 && next & 0xE0 != 0xE0 => {}
+                _ => dest.push(0x00),
+            }
+        }
+    }
+
+    dest
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::core::io::BufStream;
+    use crate::id3v2::tag::{SaveOptions, SaveVersion};
     use crate::id3v2::Tag;
     use std::env;
 
+    #[test]
+    fn encode_sync_guards() {
+        let data = b"\xFF\xE0\x16\xFF\x00\x16\xFF\x16\xFF";
+        let encoded = encode(data);
+
+        assert_eq!(encoded, b"\xFF\x00\xE0\x16\xFF\x00\x00\x16\xFF\x16\xFF\x00");
+        assert_eq!(decode(&mut BufStream::new(&encoded)), data);
+    }
+
     #[test]
     fn decode_unsync_data() {
         // Instead of directly using syncdata::decode, its nicer to have an authentic file
@@ -108,4 +149,60 @@ mod tests {
         assert_eq!(tag.frames["TRCK"].to_string(), "03");
         assert_eq!(tag.frames["TLEN"].to_string(), "216000");
     }
+
+    #[test]
+    fn encode_unsync_tag_v3() {
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/unsync.mp3";
+        let mut tag = Tag::open(path).unwrap();
+
+        let out = env::temp_dir().join("musikr_unsync_v3_out.mp3");
+        std::fs::write(&out, []).unwrap();
+
+        let options = SaveOptions {
+            unsync: true,
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        // The unsynchronization flag should be set on the tag header.
+        let data = std::fs::read(&out).unwrap();
+        assert_eq!(data[3], 3);
+        assert_eq!(data[5] & 0x80, 0x80);
+
+        unsync_ensure(&Tag::open(&out).unwrap());
+    }
+
+    #[test]
+    fn encode_unsync_tag_v4() {
+        let path = env::var("CARGO_MANIFEST_DIR").unwrap() + "/res/test/unsync.mp3";
+        let mut tag = Tag::open(path).unwrap();
+        tag.update(SaveVersion::V24);
+
+        let out = env::temp_dir().join("musikr_unsync_v4_out.mp3");
+        std::fs::write(&out, []).unwrap();
+
+        let options = SaveOptions {
+            unsync: true,
+            ..Default::default()
+        };
+
+        tag.save_with(&out, &options).unwrap();
+
+        // The unsynchronization flag should be set on the tag header and the first frame.
+        let data = std::fs::read(&out).unwrap();
+        assert_eq!(data[3], 4);
+        assert_eq!(data[5] & 0x80, 0x80);
+        assert_eq!(data[19] & 0x02, 0x02);
+
+        unsync_ensure(&Tag::open(&out).unwrap());
+    }
+
+    fn unsync_ensure(tag: &Tag) {
+        assert_eq!(tag.frames["TIT2"].to_string(), "My babe just cares for me");
+        assert_eq!(tag.frames["TPE1"].to_string(), "Nina Simone");
+        assert_eq!(tag.frames["TALB"].to_string(), "100% Jazz");
+        assert_eq!(tag.frames["TRCK"].to_string(), "03");
+        assert_eq!(tag.frames["TLEN"].to_string(), "216000");
+    }
 }
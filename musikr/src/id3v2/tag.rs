@@ -2,12 +2,17 @@
 //!
 //!
 
+use crate::core::crc::Crc32;
 use crate::core::io::BufStream;
-use crate::id3v2::{syncdata, ParseError, ParseResult};
+use crate::core::string::Encoding;
+use crate::id3v2::crypto::{FrameCipher, FrameSigner};
+use crate::id3v2::frames::FrameId;
+use crate::id3v2::{syncdata, MalformedReason, ParseError, ParseResult};
 use log::error;
-use std::fmt::{self, Display, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
 
 const ID: &[u8] = b"ID3";
+const FOOTER_ID: &[u8] = b"3DI";
 
 #[derive(Clone, Debug)]
 pub struct TagHeader {
@@ -24,6 +29,27 @@ impl TagHeader {
             return Err(ParseError::NotFound);
         }
 
+        Self::parse_body(raw)
+    }
+
+    pub(crate) fn parse_footer(raw: [u8; 10]) -> ParseResult<Self> {
+        // A footer is a copy of the header with a reversed identifier.
+        if &raw[0..3] != FOOTER_ID {
+            return Err(ParseError::NotFound);
+        }
+
+        let header = Self::parse_body(raw)?;
+
+        // Footers were only added in ID3v2.4, and they must have the footer flag set.
+        if header.version != Version::V24 || !header.flags.footer {
+            error!("found an invalid ID3v2 footer");
+            return Err(ParseError::malformed(MalformedReason::BadTagHeader));
+        }
+
+        Ok(header)
+    }
+
+    fn parse_body(raw: [u8; 10]) -> ParseResult<Self> {
         // Get the version of this tag.
         let version = match (raw[3], raw[4]) {
             (2, 0) => Version::V22,
@@ -43,7 +69,7 @@ impl TagHeader {
             || (version == Version::V24 && flags & 0x0f != 0)
         {
             error!("unused flags are set on the tag header");
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadTagHeader));
         }
 
         let flags = TagFlags {
@@ -59,7 +85,7 @@ impl TagHeader {
         // ID3v2 tags must be at least 1 byte and never more than 256mb.
         if tag_size == 0 || tag_size > 256_000_000 {
             error!("tag size can only be 1b..256mb");
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadTagHeader));
         }
 
         Ok(Self {
@@ -69,10 +95,20 @@ impl TagHeader {
         })
     }
 
-    pub(crate) fn render(&mut self) -> [u8; 10] {
+    pub(crate) fn render(&self) -> [u8; 10] {
+        self.render_with_id(ID)
+    }
+
+    pub(crate) fn render_footer(&self) -> [u8; 10] {
+        assert_eq!(self.version, Version::V24);
+        self.render_with_id(FOOTER_ID)
+    }
+
+    fn render_with_id(&self, id: &[u8]) -> [u8; 10] {
         assert_ne!(self.version, Version::V22);
 
-        let mut header = [b'I', b'D', b'3', 0, 0, 0, 0, 0, 0, 0];
+        let mut header = [0; 10];
+        header[0..3].copy_from_slice(id);
 
         // Write out the major version. The header at this point should have
         // been upgraded, so ID3v2.2 shouldn't be a possibility.
@@ -182,6 +218,240 @@ pub enum SaveVersion {
     V24,
 }
 
+/// The location of an ID3v2 tag within a file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Placement {
+    /// The tag is at the beginning of the file. This is where nearly all tags reside.
+    Prepend,
+    /// The tag is at the end of the file, either after the audio data or right before
+    /// an ID3v1 tag. This is only supported by ID3v2.4, and requires the tag to have
+    /// a footer.
+    Append,
+}
+
+/// Options for how a tag should be written.
+///
+/// The defaults of these options will keep the placement and footer of the tag as
+/// it was read. New tags will be prepended without a footer.
+#[derive(Clone, Debug, Default)]
+pub struct SaveOptions<'a> {
+    /// Where to write the tag in the file. Appending a tag is only supported on ID3v2.4
+    /// tags, and will fall back to prepending otherwise.
+    pub placement: Option<Placement>,
+    /// Whether to write an ID3v2.4 footer. A footer will always be written if the tag is
+    /// appended, and never written if the tag is ID3v2.3. No padding is added to a tag with
+    /// a footer.
+    pub footer: Option<bool>,
+    /// Whether to apply unsynchronization to the tag. On ID3v2.3, this is applied to the entire
+    /// tag body, while on ID3v2.4 this is applied to each frame. This is only useful for
+    /// compatibility with old software that cannot handle false MPEG syncs in a tag.
+    pub unsync: bool,
+    /// Which frames to compress. Compression requires the `id3v2_compression` feature, and
+    /// will be skipped if it is not enabled.
+    pub compression: CompressionPolicy,
+    /// Whether to write a CRC-32 of the tag body. This will add an extended header to the tag if
+    /// one is not present. A CRC-32 that is already present in the extended header will always be
+    /// recomputed, regardless of this option.
+    pub crc32: bool,
+    /// Whether to enforce the [`Restrictions`](Restrictions) in the extended header. Text
+    /// frames will be re-encoded and truncated, and pictures that are not allowed will be
+    /// removed. If the tag has too many frames or is too large, the tag will not be saved.
+    /// Image size restrictions are not enforced.
+    pub restrict: bool,
+    /// The [`FrameCipher`](crate::id3v2::crypto::FrameCipher) used to encrypt frames that have
+    /// an [encryption method](crate::id3v2::frames::FrameFlags::encryption). If this is not
+    /// present, or if an encrypted frame is embedded in a chapter frame, saving will fail with
+    /// [`SaveError::Unencrypted`](crate::id3v2::SaveError::Unencrypted).
+    pub cipher: Option<&'a dyn FrameCipher>,
+    /// The [`FrameSigner`](crate::id3v2::crypto::FrameSigner) used to re-sign every
+    /// [`SignatureFrame`](crate::id3v2::frames::SignatureFrame) in the tag. If this is not
+    /// present, signatures will be written as-is, and will likely no longer be valid.
+    pub signer: Option<&'a dyn FrameSigner>,
+    /// Whether the audio data of the file was altered since the tag was read. If so, frames
+    /// marked with [`FrameFlags::discard_on_file_alter`](crate::id3v2::frames::FrameFlags::discard_on_file_alter)
+    /// will be dropped, alongside any unknown frames with the same flag.
+    pub file_altered: bool,
+    /// Whether to save the tag even if frames marked with
+    /// [`FrameFlags::read_only`](crate::id3v2::frames::FrameFlags::read_only) were edited or
+    /// removed. Otherwise, the tag will not be saved.
+    pub edit_read_only: bool,
+    /// How frames with no ID3v2.3 analogue are handled when the tag is downgraded or re-saved
+    /// as ID3v2.3.
+    pub compatibility: CompatibilityPolicy,
+    /// Whether to save the tag atomically. The file will be copied to a temporary file in the
+    /// same directory, which is then written to and renamed over the original file, keeping
+    /// its permissions and timestamps. The owner and group of the file are not kept. This is
+    /// slower, but the original file is never left half-written if saving is interrupted. Only
+    /// applies to existing files.
+    pub atomic: bool,
+    /// Whether to keep a copy of the original file with a `.bak` extension added to its name.
+    /// An existing backup will be overwritten. Only applies to atomic saves.
+    pub backup: bool,
+    /// How much padding to add after the tag.
+    pub padding: PaddingPolicy<'a>,
+    /// The IDs to write unknown ID3v2.2 frames with, as ID3v2.2 frame IDs cannot be written
+    /// to newer tags. Unknown ID3v2.2 frames that are not in this list will be dropped.
+    ///
+    /// Other unknown frames are converted when the tag is upgraded or downgraded, unless they
+    /// are compressed, encrypted, unsynchronized, or have a data length indicator. In that
+    /// case, the frame data depends on the version and the frame is dropped.
+    pub legacy_ids: &'a [([u8; 3], FrameId)],
+}
+
+/// Options for how a tag should be read.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OpenOptions<'a> {
+    /// The [`FrameCipher`](crate::id3v2::crypto::FrameCipher) used to decrypt encrypted
+    /// frames. If this is not present, encrypted frames will be left as unknown frames.
+    pub cipher: Option<&'a dyn FrameCipher>,
+    /// The [`FrameSigner`](crate::id3v2::crypto::FrameSigner) used to verify signed groups
+    /// of frames. The result can be found with
+    /// [`Tag::signatures_match`](crate::id3v2::Tag::signatures_match).
+    pub signer: Option<&'a dyn FrameSigner>,
+    /// Whether to recover from malformed frames instead of stopping at them. Frames that
+    /// could not be parsed are dropped, and if a frame header is malformed, the tag is scanned
+    /// for the next valid frame. Each problem is recorded in
+    /// [`Tag::diagnostics`](crate::id3v2::Tag::diagnostics).
+    pub lenient: bool,
+}
+
+/// A report of the changes made to a tag when it was saved.
+#[derive(Clone, Debug, Default)]
+pub struct SaveReport {
+    /// The changes made so that the tag would conform to its [`Restrictions`](Restrictions).
+    /// This is only populated if [`SaveOptions::restrict`](SaveOptions::restrict) was enabled.
+    pub restrictions: Vec<RestrictionChange>,
+    /// The problems found when the tag was updated to the version it was saved as.
+    pub update: UpdateReport,
+}
+
+/// A report of the problems found when a tag was updated to another version.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UpdateReport {
+    /// The timestamps that could not be converted, alongside the ID of the frame they were
+    /// in. If part of a timestamp was valid, then that part is still converted.
+    pub malformed_timestamps: Vec<(FrameId, String)>,
+}
+
+/// A change made to a tag so that it would conform to its [`Restrictions`](Restrictions).
+#[derive(Clone, Debug, PartialEq)]
+pub enum RestrictionChange {
+    /// The encoding of a frame was changed.
+    Reencoded {
+        key: String,
+        from: Encoding,
+        to: Encoding,
+    },
+    /// A string in a frame was truncated to `len` characters.
+    Truncated { key: String, len: usize },
+    /// A picture frame was removed, as its format was not allowed.
+    RemovedPicture { key: String, mime: String },
+    /// The padding of the tag was reduced so that the tag would fit within the size limit.
+    ReducedPadding { from: u64, to: u64 },
+}
+
+/// The policy for compressing frames when a tag is written.
+///
+/// Frames will only be compressed if doing so makes them smaller. Frames embedded in
+/// chapter frames are never compressed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum CompressionPolicy {
+    /// No frames are compressed.
+    #[default]
+    None,
+    /// All frames are compressed.
+    All,
+    /// Frames with a body larger than the given amount of bytes are compressed.
+    LargerThan(usize),
+    /// Only frames with the given IDs are compressed.
+    Frames(Vec<FrameId>),
+}
+
+impl CompressionPolicy {
+    pub(crate) fn applies(&self, frame_id: FrameId, size: usize) -> bool {
+        match self {
+            Self::None => false,
+            Self::All => true,
+            Self::LargerThan(limit) => size > *limit,
+            Self::Frames(ids) => ids.contains(&frame_id),
+        }
+    }
+}
+
+/// The policy for how much padding to write after a tag.
+///
+/// Padding lets a tag grow in later saves without the audio data having to move, which is
+/// much faster on large files. Tags with a footer and empty tags are never padded, regardless
+/// of the policy.
+#[derive(Clone, Copy, Default)]
+pub enum PaddingPolicy<'a> {
+    /// If the tag is smaller than the previous tag, the remaining space is used as padding,
+    /// up to 1% of the file size. If the tag is larger, 1 KiB of padding is used.
+    #[default]
+    Default,
+    /// No padding is written.
+    None,
+    /// The given amount of bytes is always used as padding.
+    Fixed(u64),
+    /// The given percentage of the file size is always used as padding.
+    Percent(f64),
+    /// The tag is padded to the size of the previous tag, so that the audio data does not
+    /// have to move. If the tag is larger than the previous tag, then 1 KiB of padding is used.
+    Keep,
+    /// A function that is given the size of the previous tag, the size of the new tag
+    /// without padding, and the length of the file, and returns the amount of padding to use.
+    Custom(&'a dyn Fn(u64, u64, u64) -> u64),
+}
+
+impl PaddingPolicy<'_> {
+    pub(crate) fn padding(&self, old_size: u64, tag_size: u64, len: u64) -> u64 {
+        let delta = u64::checked_sub(old_size, tag_size);
+
+        let padding = match self {
+            Self::Default => match delta {
+                Some(delta) => u64::min(delta, len / 100),
+                None => 1024,
+            },
+            Self::None => 0,
+            Self::Fixed(size) => *size,
+            Self::Percent(percent) => (len as f64 * percent / 100.0).max(0.0) as u64,
+            Self::Keep => delta.unwrap_or(1024),
+            Self::Custom(func) => func(old_size, tag_size, len),
+        };
+
+        // Tags can never be more than 256mb, so neither can their padding. This also keeps
+        // huge amounts of padding from overflowing once they are added to the tag size.
+        u64::min(padding, 256_000_000u64.saturating_sub(tag_size))
+    }
+}
+
+impl Debug for PaddingPolicy<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Default => write![f, "Default"],
+            Self::None => write![f, "None"],
+            Self::Fixed(size) => f.debug_tuple("Fixed").field(size).finish(),
+            Self::Percent(percent) => f.debug_tuple("Percent").field(percent).finish(),
+            Self::Keep => write![f, "Keep"],
+            Self::Custom(_) => write![f, "Custom"],
+        }
+    }
+}
+
+/// The policy for frames that have no analogue when a tag is downgraded to ID3v2.3.
+///
+/// See [`Tag::update`](crate::id3v2::Tag::update) for the frames affected by this policy.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CompatibilityPolicy {
+    /// Frames that are not in the ID3v2.3 spec are dropped.
+    #[default]
+    Strict,
+    /// Frames that are not in the ID3v2.3 spec but are widely understood anyway, such as
+    /// the iTunes sort order frames, are kept. Other text frames become
+    /// [`UserTextFrame`](crate::id3v2::frames::UserTextFrame)s.
+    Compatible,
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct ExtendedHeader {
     pub padding_size: Option<u32>,
@@ -222,6 +492,31 @@ impl ExtendedHeader {
             }
         }
     }
+
+    /// Updates the padding size and CRC-32 of this header to match the tag body that will
+    /// follow it. `frames` is the frame data before any ID3v2.3 unsynchronization.
+    pub(crate) fn update_body(&mut self, version: Version, frames: &[u8], padding: usize) {
+        if version == Version::V23 {
+            self.padding_size = Some(padding as u32);
+        }
+
+        if self.crc32.is_some() {
+            self.crc32 = Some(body_crc(version, frames, padding));
+        }
+    }
+}
+
+/// Computes the CRC-32 of a tag body. ID3v2.3 only computes the CRC over the frames, while
+/// ID3v2.4 also includes the padding.
+pub(crate) fn body_crc(version: Version, frames: &[u8], padding: usize) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(frames);
+
+    if version == Version::V24 {
+        crc.update(&vec![0; padding]);
+    }
+
+    crc.finish()
 }
 
 fn parse_ext_v3(stream: &mut BufStream) -> ParseResult<ExtendedHeader> {
@@ -230,7 +525,7 @@ fn parse_ext_v3(stream: &mut BufStream) -> ParseResult<ExtendedHeader> {
     // The extended header should be 6 or 10 bytes
     if size != 6 && size != 10 {
         error!("ID3v2.3 extended headers are 6 or 10 bytes, found {}", size);
-        return Err(ParseError::MalformedData);
+        return Err(ParseError::malformed(MalformedReason::BadExtendedHeader));
     }
 
     let flags = stream.read_be_u16()?;
@@ -255,13 +550,13 @@ fn parse_ext_v4(stream: &mut BufStream) -> ParseResult<ExtendedHeader> {
     // An extended header can be at most between 6 and 15 bytes
     if !(6..=15).contains(&size) {
         error!("ID3v2.4 extended headers can only be 6 to 15 bytes long");
-        return Err(ParseError::MalformedData);
+        return Err(ParseError::malformed(MalformedReason::BadExtendedHeader));
     }
 
     // The flag count is always 1.
     if stream.read_u8()? != 1 {
         error!("ID3v2.4 extended headers must have a flag count of 1");
-        return Err(ParseError::MalformedData);
+        return Err(ParseError::malformed(MalformedReason::BadExtendedHeader));
     }
 
     let mut header = ExtendedHeader {
@@ -278,7 +573,7 @@ fn parse_ext_v4(stream: &mut BufStream) -> ParseResult<ExtendedHeader> {
         // Flag must have no accompanying data.
         if stream.read_u8()? != 0 {
             error!("invalid is_update length");
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadExtendedHeader));
         }
 
         header.is_update = true;
@@ -289,19 +584,19 @@ fn parse_ext_v4(stream: &mut BufStream) -> ParseResult<ExtendedHeader> {
         // CRC-32 data must be a 35-bit syncsafe integer.
         if stream.read_u8()? != 5 {
             error!("invalid CRC-32 length");
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadExtendedHeader));
         }
 
         header.crc32 = Some(syncdata::to_u35(stream.read_array()?));
     }
 
-    // Tag restrictions. Musikr doesn't really do anything with these since according to the spec
-    // they are only flags for when the tag was *encoded*, now how it should *decode*.
+    // Tag restrictions. According to the spec these are only flags for when the tag was *encoded*,
+    // not how it should *decode*, so they are only enforced when a tag is saved.
     if flags & 0x10 != 0 {
         // Restrictions must be 1 byte in length.
         if stream.read_u8()? != 1 {
             error!("invalid restrictions length");
-            return Err(ParseError::MalformedData);
+            return Err(ParseError::malformed(MalformedReason::BadExtendedHeader));
         }
 
         let restrictions = stream.read_u8()?;
@@ -334,7 +629,7 @@ fn parse_ext_v4(stream: &mut BufStream) -> ParseResult<ExtendedHeader> {
             _ => unreachable!(),
         };
 
-        let image_size = match (restrictions & 0x3) >> 1 {
+        let image_size = match restrictions & 0x3 {
             0 => ImageSizeRestriction::None,
             1 => ImageSizeRestriction::LessThan256x256,
             2 => ImageSizeRestriction::LessThan64x64,
@@ -403,7 +698,7 @@ fn render_ext_v4(header: &ExtendedHeader) -> Vec<u8> {
         bits |= (restrictions.text_encoding as u8) << 5;
         bits |= (restrictions.text_size as u8) << 3;
         bits |= (restrictions.image_encoding as u8) << 2;
-        bits |= (restrictions.image_size as u8) << 1;
+        bits |= restrictions.image_size as u8;
 
         data.push(bits)
     }
@@ -428,6 +723,27 @@ pub enum TagSizeRestriction {
     Max32Frames4Kb = 3,
 }
 
+impl TagSizeRestriction {
+    /// Returns the maximum amount of frames allowed in the tag.
+    pub fn max_frames(&self) -> usize {
+        match self {
+            Self::Max128Frames1Mb => 128,
+            Self::Max64Frames128Kb => 64,
+            Self::Max32Frames40Kb | Self::Max32Frames4Kb => 32,
+        }
+    }
+
+    /// Returns the maximum total size of the tag, in bytes.
+    pub fn max_size(&self) -> u64 {
+        match self {
+            Self::Max128Frames1Mb => 1024 * 1024,
+            Self::Max64Frames128Kb => 128 * 1024,
+            Self::Max32Frames40Kb => 40 * 1024,
+            Self::Max32Frames4Kb => 4 * 1024,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TextEncodingRestriction {
     None = 0,
@@ -442,6 +758,18 @@ pub enum TextSizeRestriction {
     LessThan30Chars = 3,
 }
 
+impl TextSizeRestriction {
+    /// Returns the maximum amount of characters allowed in a string, if any.
+    pub fn max_chars(&self) -> Option<usize> {
+        match self {
+            Self::None => None,
+            Self::LessThan1024Chars => Some(1024),
+            Self::LessThan128Chars => Some(128),
+            Self::LessThan30Chars => Some(30),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ImageEncodingRestriction {
     None = 0,
@@ -464,6 +792,31 @@ mod tests {
     const EXT_DATA_V3: &[u8] = b"\x00\x00\x00\x0A\x80\x00\xAB\xCD\xEF\x16\x16\x16\x16\x16";
     const EXT_DATA_V4: &[u8] = b"\x00\x00\x00\x0F\x01\x70\x00\x05\x07\x5E\x37\x5E\x16\x01\xB4";
 
+    #[test]
+    fn padding_policy() {
+        assert_eq!(PaddingPolicy::Default.padding(2048, 1024, 200_000), 1024);
+        assert_eq!(PaddingPolicy::Default.padding(2048, 1024, 50_000), 500);
+        assert_eq!(PaddingPolicy::Default.padding(1024, 2048, 50_000), 1024);
+        assert_eq!(PaddingPolicy::None.padding(2048, 1024, 50_000), 0);
+        assert_eq!(PaddingPolicy::Fixed(16).padding(2048, 1024, 50_000), 16);
+        assert_eq!(PaddingPolicy::Percent(5.0).padding(0, 0, 50_000), 2500);
+        assert_eq!(PaddingPolicy::Keep.padding(2048, 1024, 50_000), 1024);
+        assert_eq!(PaddingPolicy::Keep.padding(1024, 2048, 50_000), 1024);
+
+        let custom = |old, new, len| old + new + len;
+        assert_eq!(PaddingPolicy::Custom(&custom).padding(1, 2, 3), 6);
+
+        // Padding should never push the tag past 256mb.
+        let max = 256_000_000 - 1024;
+        let custom = |_, _, _| u64::MAX;
+        assert_eq!(PaddingPolicy::Custom(&custom).padding(0, 1024, 0), max);
+        assert_eq!(PaddingPolicy::Fixed(u64::MAX).padding(0, 1024, 0), max);
+        let infinite = PaddingPolicy::Percent(f64::INFINITY);
+        assert_eq!(infinite.padding(0, 1024, 1), max);
+        assert_eq!(PaddingPolicy::Percent(f64::NAN).padding(0, 1024, 1), 0);
+        assert_eq!(PaddingPolicy::Fixed(1024).padding(0, 300_000_000, 0), 0);
+    }
+
     #[test]
     fn parse_v3_tag_header() {
         let data = b"\x49\x44\x33\x03\x00\xA0\x00\x08\x49\x30";
@@ -493,6 +846,30 @@ mod tests {
         assert!(flags.footer);
     }
 
+    #[test]
+    fn parse_v4_tag_footer() {
+        let data = b"\x33\x44\x49\x04\x00\x10\x00\x08\x49\x30";
+        let footer = TagHeader::parse_footer(*data).unwrap();
+
+        assert_eq!(footer.size(), 140464);
+        assert_eq!(footer.version(), Version::V24);
+        assert!(footer.flags().footer);
+
+        assert_eq!(footer.render_footer(), *data);
+        assert_eq!(&footer.render()[0..3], b"ID3");
+        assert_eq!(&footer.render()[3..], &data[3..]);
+    }
+
+    #[test]
+    fn parse_invalid_tag_footer() {
+        // Wrong identifier
+        assert!(TagHeader::parse_footer(*b"\x49\x44\x33\x04\x00\x10\x00\x08\x49\x30").is_err());
+        // ID3v2.3 has no footers
+        assert!(TagHeader::parse_footer(*b"\x33\x44\x49\x03\x00\x00\x00\x08\x49\x30").is_err());
+        // Footer flag is unset
+        assert!(TagHeader::parse_footer(*b"\x33\x44\x49\x04\x00\x00\x00\x08\x49\x30").is_err());
+    }
+
     #[test]
     fn parse_v3_ext_header() {
         let header = ExtendedHeader::parse(&mut BufStream::new(EXT_DATA_V3), Version::V23).unwrap();